@@ -0,0 +1,133 @@
+use crate::error::{Error, Result};
+use crate::{Answer, Part};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Parts {
+    part1: Option<i64>,
+    part2: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub day: u32,
+    pub input: String,
+    pub answers: Vec<(Part, i64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Regression { expected: i64, actual: i128 },
+    Fail(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Regression { expected, actual } => write!(f, "regression\n  - {}\n  + {}", expected, actual),
+            Verdict::Fail(reason) => write!(f, "fail: {}", reason),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Expected>> {
+    let days: BTreeMap<String, BTreeMap<String, Parts>> = toml::from_str(text).map_err(|e| match e.span() {
+        Some(span) => Error::parse_at(text, &text[span.start..], e.message()),
+        None => Error::parse(1, 1, e.message()),
+    })?;
+
+    let mut expected = Vec::new();
+    for (key, inputs) in days {
+        let day = key
+            .strip_prefix("day")
+            .and_then(|n| n.parse::<u32>().ok())
+            .ok_or_else(|| Error::parse_at(text, &text[text.find(&key).unwrap_or(0)..], format!("invalid day `{}`", key)))?;
+        for (input, parts) in inputs {
+            let answers = [(Part::One, parts.part1), (Part::Two, parts.part2)]
+                .into_iter()
+                .filter_map(|(part, answer)| answer.map(|answer| (part, answer)))
+                .collect();
+            expected.push(Expected { day, input, answers });
+        }
+    }
+    expected.sort_by_key(|e| e.day);
+    Ok(expected)
+}
+
+pub fn load(path: &Path) -> Result<Vec<Expected>> {
+    let text = std::fs::read_to_string(path).map_err(|error| Error::Io {
+        input: path.display().to_string(),
+        error,
+    })?;
+    parse(&text)
+}
+
+impl Expected {
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("data/day{}/{}.txt", self.day, self.input))
+    }
+
+    pub fn verify(&self) -> Vec<(Part, Verdict)> {
+        let parts: Vec<Part> = self.answers.iter().map(|&(part, _)| part).collect();
+        let answers = std::fs::read_to_string(self.path())
+            .map_err(|error| Error::Io {
+                input: self.path().display().to_string(),
+                error,
+            })
+            .and_then(|input| crate::solve(self.day, &parts, &input));
+        let answers = match answers {
+            Ok(answers) => answers,
+            Err(e) => return parts.into_iter().map(|part| (part, Verdict::Fail(e.to_string()))).collect(),
+        };
+        self.answers
+            .iter()
+            .zip(answers)
+            .map(|(&(part, expected), answer)| {
+                let verdict = match answer {
+                    Ok(Answer::Number(actual)) if actual == expected.into() => Verdict::Pass,
+                    Ok(Answer::Number(actual)) => Verdict::Regression { expected, actual },
+                    Ok(Answer::Unimplemented) => Verdict::Fail(Answer::Unimplemented.to_string()),
+                    Err(e) => Verdict::Fail(e.to_string()),
+                };
+                (part, verdict)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expected, Verdict};
+    use crate::Part;
+
+    #[test]
+    fn t1() {
+        let expected = super::parse("[day10]\ninput2 = { part2 = 435 }\n[day9]\ninput1 = { part1 = 114, part2 = 2 }\n").unwrap();
+        assert_eq!(expected, vec![
+            Expected { day: 9, input: "input1".to_string(), answers: vec![(Part::One, 114), (Part::Two, 2)] },
+            Expected { day: 10, input: "input2".to_string(), answers: vec![(Part::Two, 435)] },
+        ]);
+
+        let e = super::parse("[day9]\ninput1 = { part3 = 1 }\n").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 2"));
+        assert!(super::parse("[week9]\ninput1 = { part1 = 1 }\n").is_err());
+    }
+
+    #[test]
+    fn t2() {
+        let expected = Expected { day: 9, input: "input1".to_string(), answers: vec![(Part::One, 114), (Part::Two, 3)] };
+        assert_eq!(expected.verify(), vec![
+            (Part::One, Verdict::Pass),
+            (Part::Two, Verdict::Regression { expected: 3, actual: 2 }),
+        ]);
+
+        let expected = Expected { day: 9, input: "input99".to_string(), answers: vec![(Part::One, 114)] };
+        assert!(matches!(&expected.verify()[0].1, Verdict::Fail(_)));
+    }
+}
@@ -0,0 +1,135 @@
+use aoc23::input::example_path;
+use aoc23::Solution;
+use aoc23::{
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::Day7,
+    day8::Day8,
+    day9::Day9,
+    day10::Day10,
+    day11::Day11,
+    day12::{self, Day12},
+    day13::Day13,
+    day14::Day14,
+    day15::Day15,
+    day16::Day16,
+    day17::Day17,
+    day18::Day18,
+    day19::Day19,
+};
+use divan::{black_box, Bencher};
+
+fn main() {
+    divan::main();
+}
+
+fn input<S: Solution>() -> String {
+    std::fs::read_to_string(example_path(S::DAY, 2)).unwrap()
+}
+
+#[divan::bench(types = [Day1, Day2, Day3, Day4, Day5, Day6, Day7, Day8, Day9, Day10, Day11, Day12, Day13, Day14, Day15, Day16, Day17, Day18, Day19], max_time = 5)]
+fn parse<S: Solution>(bencher: Bencher) {
+    let input = input::<S>();
+    bencher.bench_local(|| S::parse(black_box(&input)));
+}
+
+#[divan::bench(types = [Day1, Day2, Day3, Day4, Day5, Day6, Day7, Day8, Day9, Day10, Day11, Day12, Day13, Day14, Day15, Day16, Day17, Day18, Day19], max_time = 5)]
+fn part1<S: Solution>(bencher: Bencher) {
+    let input = input::<S>();
+    let model = S::parse(&input).unwrap();
+    bencher.bench_local(|| S::part1(black_box(&model)));
+}
+
+#[divan::bench(types = [Day1, Day2, Day3, Day4, Day5, Day6, Day7, Day8, Day9, Day10, Day11, Day12, Day13, Day14, Day15, Day16, Day17, Day18, Day19], max_time = 5)]
+fn part2<S: Solution>(bencher: Bencher) {
+    let input = input::<S>();
+    let model = S::parse(&input).unwrap();
+    bencher.bench_local(|| S::part2(black_box(&model)));
+}
+
+/// The HashMap-memoised recursion day12 used before its DP table, kept here
+/// to measure the DP against.
+mod baseline {
+    use std::collections::HashMap;
+
+    type Cache = HashMap<(String, Vec<usize>), usize>;
+
+    pub fn arrangements(seed: &str, groups: &[usize]) -> usize {
+        recurse(seed, groups, &mut Cache::new())
+    }
+
+    fn on_dot(seed: &str, groups: &[usize], cache: &mut Cache) -> usize {
+        if !seed.is_empty() {
+            let res = recurse(&seed[1..], groups, cache);
+            cache.insert((seed[1..].to_owned(), groups.to_vec()), res);
+            return res;
+        }
+        let res = groups.is_empty() as usize;
+        cache.insert((seed.to_owned(), groups.to_vec()), res);
+        res
+    }
+
+    fn on_hash(seed: &str, groups: &[usize], cache: &mut Cache) -> usize {
+        if groups.is_empty()
+            || groups.iter().sum::<usize>() > seed.len()
+            || seed[0..groups[0]].contains('.')
+            || (seed.len() > groups[0] && seed.as_bytes()[groups[0]] == b'#')
+        {
+            cache.insert((seed.to_owned(), groups.to_vec()), 0);
+            return 0;
+        }
+        if seed.len() > groups[0] {
+            let res = recurse(&seed[groups[0] + 1..], &groups[1..], cache);
+            cache.insert((seed[groups[0] + 1..].to_owned(), groups[1..].to_vec()), res);
+            return res;
+        }
+        cache.insert((seed.to_owned(), groups.to_vec()), 1);
+        1
+    }
+
+    fn recurse(seed: &str, groups: &[usize], cache: &mut Cache) -> usize {
+        if let Some(&res) = cache.get(&(seed.to_owned(), groups.to_vec())) {
+            return res;
+        }
+        match seed.chars().next() {
+            Some('.') => on_dot(seed, groups, cache),
+            Some('#') => on_hash(seed, groups, cache),
+            Some('?') => on_dot(seed, groups, cache) + on_hash(seed, groups, cache),
+            _ => groups.is_empty() as usize,
+        }
+    }
+}
+
+const UNFOLD: [usize; 4] = [1, 2, 3, 5];
+
+fn unfolded(factor: usize) -> Vec<day12::Record> {
+    let input = input::<Day12>();
+    Day12::parse(&input).unwrap().records().iter().map(|record| record.unfold(factor)).collect()
+}
+
+#[divan::bench(consts = UNFOLD, max_time = 5)]
+fn day12_unfolded<const FACTOR: usize>(bencher: Bencher) {
+    let records = unfolded(FACTOR);
+    bencher.bench_local(|| day12::total_arrangements(black_box(&records)));
+}
+
+#[divan::bench(consts = UNFOLD, max_time = 5)]
+fn day12_unfolded_sequential<const FACTOR: usize>(bencher: Bencher) {
+    let records = unfolded(FACTOR);
+    bencher.bench_local(|| black_box(&records).iter().map(|record| record.arrangements().unwrap()).sum::<u64>());
+}
+
+#[divan::bench(consts = UNFOLD, max_time = 5)]
+fn day12_unfolded_baseline<const FACTOR: usize>(bencher: Bencher) {
+    let records = unfolded(FACTOR);
+    bencher.bench_local(|| {
+        black_box(&records)
+            .iter()
+            .map(|record| baseline::arrangements(record.springs(), record.groups()))
+            .sum::<usize>()
+    });
+}
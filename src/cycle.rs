@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Brent's algorithm, see [`brent`].
+pub fn find_cycle<T: Clone + Eq>(start: &T, step: impl Fn(&T) -> T) -> (usize, usize) {
+    brent(start, step)
+}
+
+/// Floyd's tortoise and hare. Returns `(mu, lambda)`: the index of the first
+/// state on the cycle and the length of the cycle. `step` must eventually
+/// repeat a state, or this never returns.
+pub fn floyd<T: Clone + Eq>(start: &T, step: impl Fn(&T) -> T) -> (usize, usize) {
+    let mut tortoise = step(start);
+    let mut hare = step(&step(start));
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut mu = 0;
+    tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+
+    let mut lambda = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        lambda += 1;
+    }
+    (mu, lambda)
+}
+
+/// Brent's algorithm: finds the cycle length first with fewer steps than
+/// Floyd's, then the start of the cycle. Same result and caveat as [`floyd`].
+pub fn brent<T: Clone + Eq>(start: &T, step: impl Fn(&T) -> T) -> (usize, usize) {
+    let mut power = 1;
+    let mut lambda = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+        hare = step(&hare);
+        lambda += 1;
+    }
+
+    tortoise = start.clone();
+    hare = start.clone();
+    for _ in 0..lambda {
+        hare = step(&hare);
+    }
+    let mut mu = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+    (mu, lambda)
+}
+
+/// Remembers every state, so each one is computed only once at the cost of
+/// keeping them all. Same result and caveat as [`floyd`].
+pub fn hashed<T: Clone + Eq + Hash>(start: &T, step: impl Fn(&T) -> T) -> (usize, usize) {
+    let mut seen = HashMap::new();
+    let mut state = start.clone();
+    for i in 0.. {
+        if let Some(&mu) = seen.get(&state) {
+            return (mu, i - mu);
+        }
+        let next = step(&state);
+        seen.insert(state, i);
+        state = next;
+    }
+    unreachable!()
+}
+
+/// The state after `n` steps, skipping whole laps of the `(mu, lambda)` cycle.
+pub fn state_at<T: Clone>(start: &T, step: impl Fn(&T) -> T, (mu, lambda): (usize, usize), n: usize) -> T {
+    let n = if n < mu { n } else { mu + (n - mu) % lambda };
+    let mut state = start.clone();
+    for _ in 0..n {
+        state = step(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn t1() {
+        let step = |x: &u32| (x * x + 1) % 255;
+        let expected = super::hashed(&3, step);
+        assert_eq!(super::floyd(&3, step), expected);
+        assert_eq!(super::brent(&3, step), expected);
+        assert_eq!(super::find_cycle(&3, step), expected);
+
+        let mut state = 3;
+        for n in 0..500 {
+            assert_eq!(super::state_at(&3, step, expected, n), state);
+            state = step(&state);
+        }
+    }
+
+    #[test]
+    fn t2() {
+        let step = |x: &u64| (x + 1) % 5;
+        assert_eq!(super::floyd(&0, step), (0, 5));
+        assert_eq!(super::brent(&0, step), (0, 5));
+        assert_eq!(super::hashed(&0, step), (0, 5));
+
+        let step = |x: &u64| if *x < 10 { x + 1 } else { 10 };
+        assert_eq!(super::brent(&0, step), (10, 1));
+        assert_eq!(super::state_at(&0, step, (10, 1), 1_000_000_000_000), 10);
+    }
+}
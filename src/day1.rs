@@ -1,3 +1,6 @@
+use crate::{Answer, Solution};
+use crate::error::Result;
+
 fn fancy_str_to_number_part1(input: &str) -> Option<char> {
     let input = input.to_lowercase();
     let mut result = None;
@@ -68,8 +71,8 @@ fn compute_calibration_value_part1(input: &str) -> i32 {
                 b = tmp;
             }
         }
-        if a.is_some() && b.is_some() {
-            let line_value = format!("{}{}", a.unwrap(), b.unwrap());
+        if let (Some(a), Some(b)) = (a, b) {
+            let line_value = format!("{}{}", a, b);
             calibration_value += line_value.parse::<i32>().unwrap();
         }
     }
@@ -91,8 +94,8 @@ fn compute_calibration_value_part2(input: &str) -> i32 {
                 b = tmp;
             }
         }
-        if a.is_some() && b.is_some() {
-            let line_value = format!("{}{}", a.unwrap(), b.unwrap());
+        if let (Some(a), Some(b)) = (a, b) {
+            let line_value = format!("{}{}", a, b);
             calibration_value += line_value.parse::<i32>().unwrap();
         }
     }
@@ -100,14 +103,24 @@ fn compute_calibration_value_part2(input: &str) -> i32 {
     calibration_value
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day1/input2.txt").unwrap();
-    println!("Day 1, Part 1: {}", compute_calibration_value_part1(&input));
-}
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u32 = 1;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day1/input4.txt").unwrap();
-    println!("Day 1, Part 2: {}", compute_calibration_value_part2(&input));
+    type Model<'a> = &'a str;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        Ok(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(compute_calibration_value_part1(model).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(compute_calibration_value_part2(model).into())
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,3 @@
-#[allow(dead_code)]
-
 use nom::{
     bytes::complete::tag,
     character::complete::line_ending,
@@ -9,12 +7,15 @@ use nom::{
     combinator::map,
     IResult
 };
-use petgraph::graph::Graph;
-use petgraph::visit::Dfs;
+use petgraph::graph::{Graph, NodeIndex};
 use std::fmt;
+use crate::{Answer, Solution};
+use crate::geometry::{Point, Polygon};
+use crate::grid::Grid;
+use crate::error::{self, Error, Result};
 
-#[derive(Clone, Eq, PartialEq)]
-enum Connections {
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Connections {
     NorthSouth,
     EastWest,
     NorthEast,
@@ -26,7 +27,7 @@ enum Connections {
 }
 
 #[derive(Clone)]
-struct Cell {
+pub struct Cell {
     connections: Connections,
     row: usize,
     col: usize,
@@ -77,273 +78,185 @@ fn parse(input: &str) -> IResult<&str, Graph<Cell, usize, petgraph::Undirected>>
     )(input)?;
 
     let mut graph = Graph::<Cell, usize, petgraph::Undirected>::new_undirected();
-    for (row, rows) in lines.iter().enumerate() {
-        for (col, connections) in rows.iter().enumerate() {
-            graph.add_node(Cell {
-                connections: connections.clone(),
-                row,
-                col,
-            });
+    let nodes: Vec<Vec<NodeIndex>> = lines
+        .into_iter()
+        .enumerate()
+        .map(|(row, tiles)| {
+            tiles
+                .into_iter()
+                .enumerate()
+                .map(|(col, connections)| graph.add_node(Cell { connections, row, col }))
+                .collect()
+        })
+        .collect();
+    let node_at = |row: usize, col: usize, (d_row, d_col): (isize, isize)| {
+        nodes.get(row.checked_add_signed(d_row)?)?.get(col.checked_add_signed(d_col)?).copied()
+    };
+
+    // Two pipes are linked when each leads to the other, a pipe and the start
+    // whenever the pipe leads to it.
+    let mut edges = Vec::new();
+    for node in graph.node_indices() {
+        let cell = &graph[node];
+        for &(d_row, d_col) in cell.connections.offsets() {
+            let Some(other) = node_at(cell.row, cell.col, (d_row, d_col)) else {
+                continue;
+            };
+            let linked = match &graph[other].connections {
+                Connections::Start => true,
+                connections => node < other && connections.offsets().contains(&(-d_row, -d_col)),
+            };
+            if linked {
+                edges.push((node, other));
+            }
         }
     }
+    for (a, b) in edges {
+        graph.add_edge(a, b, 1);
+    }
 
-    for idx1 in graph.node_indices() {
-        match &graph[idx1].connections {
-            Connections::NorthSouth => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].row > 0 &&
-                        graph[*idx2].row == graph[idx1].row - 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::SouthEast ||
-                            graph[*idx2].connections == Connections::SouthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                    graph[idx1].row < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row + 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::NorthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-            Connections::EastWest => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col > 0 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col - 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::NorthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col + 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthWest ||
-                            graph[*idx2].connections == Connections::SouthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-
-            Connections::NorthEast => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].row > 0 &&
-                        graph[*idx2].row == graph[idx1].row - 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::SouthWest ||
-                            graph[*idx2].connections == Connections::SouthEast
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col + 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthWest ||
-                            graph[*idx2].connections == Connections::SouthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-            Connections::NorthWest => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].row > 0 &&
-                        graph[*idx2].row == graph[idx1].row - 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::SouthEast ||
-                            graph[*idx2].connections == Connections::SouthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col > 0 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col - 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::SouthEast
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-            Connections::SouthWest => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].row < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row + 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::NorthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col > 0 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col - 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::SouthEast
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-            Connections::SouthEast => {
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].row < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row + 1 &&
-                        graph[*idx2].col == graph[idx1].col && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::NorthSouth ||
-                            graph[*idx2].connections == Connections::NorthEast ||
-                            graph[*idx2].connections == Connections::NorthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                if let Some(node) = graph.node_indices().find(|idx2|
-                        graph[idx1].col < graph.node_count() - 1 &&
-                        graph[*idx2].row == graph[idx1].row &&
-                        graph[*idx2].col == graph[idx1].col + 1 && (
-                            graph[*idx2].connections == Connections::Start ||
-                            graph[*idx2].connections == Connections::EastWest ||
-                            graph[*idx2].connections == Connections::NorthWest ||
-                            graph[*idx2].connections == Connections::SouthWest
-                        )
-                    ) {
-                        graph.add_edge(idx1, node, 1);
-                    };
-                },
-
-            _ => {}
+    Ok((input, graph))
+}
+
+impl Connections {
+    /// The (row, col) offsets of the tiles this pipe leads to.
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Connections::NorthSouth => &[(-1, 0), (1, 0)],
+            Connections::EastWest => &[(0, -1), (0, 1)],
+            Connections::NorthEast => &[(-1, 0), (0, 1)],
+            Connections::NorthWest => &[(-1, 0), (0, -1)],
+            Connections::SouthWest => &[(1, 0), (0, -1)],
+            Connections::SouthEast => &[(1, 0), (0, 1)],
+            Connections::Ground | Connections::Start => &[],
         }
     }
 
-    Ok((input, graph))
+    fn north(&self) -> bool {
+        matches!(self, Connections::NorthSouth | Connections::NorthEast | Connections::NorthWest)
+    }
 }
 
-fn find_cycle_length(graph: &Graph<Cell, usize, petgraph::Undirected>) -> usize {
-    if let Some(s) = graph.node_indices().find(|node| {
-        graph[*node].connections == Connections::Start
-    }) {
-        for nbor in graph.neighbors(s) {
-            let mut dfs = Dfs::new(graph, nbor);
-            let mut steps = 0;
-            let mut back_to_start = false;
-            while let Some(node) = dfs.next(graph) {
-                steps += 1;
-                if node.index() == s.index() {
-                    back_to_start = true;
-                    break;
-                }
-            }
-            if back_to_start {
-                return steps/2;
+/// The loop through `S` as coordinates in walking order, starting with `S`.
+pub fn find_loop(graph: &Graph<Cell, usize, petgraph::Undirected>) -> Result<Vec<(usize, usize)>> {
+    let s = graph
+        .node_indices()
+        .find(|node| graph[*node].connections == Connections::Start)
+        .ok_or_else(|| Error::no_solution("no start found"))?;
+    for first in graph.neighbors(s) {
+        let mut path = vec![s];
+        let (mut prev, mut current) = (s, first);
+        while current != s {
+            path.push(current);
+            match graph.neighbors(current).find(|&node| node != prev) {
+                Some(next) => (prev, current) = (current, next),
+                None => break,
             }
         }
-        panic!("no loops found");
-    } else {
-        panic!("no start found");
+        if current == s && path.len() > 2 {
+            return Ok(path.iter().map(|&node| (graph[node].row, graph[node].col)).collect());
+        }
+    }
+    Err(Error::no_solution("no loop through the start found"))
+}
+
+/// The pipe hidden under `S`, from the two loop tiles next to it.
+fn start_pipe(path: &[(usize, usize)]) -> Result<Connections> {
+    let (row, col) = path[0];
+    let side = |(r, c): (usize, usize)| match (r as isize - row as isize, c as isize - col as isize) {
+        (-1, 0) => 'N',
+        (1, 0) => 'S',
+        (0, 1) => 'E',
+        _ => 'W',
+    };
+    let mut sides = [side(path[1]), side(path[path.len() - 1])];
+    sides.sort();
+    match sides {
+        ['N', 'S'] => Ok(Connections::NorthSouth),
+        ['E', 'W'] => Ok(Connections::EastWest),
+        ['E', 'N'] => Ok(Connections::NorthEast),
+        ['N', 'W'] => Ok(Connections::NorthWest),
+        ['S', 'W'] => Ok(Connections::SouthWest),
+        ['E', 'S'] => Ok(Connections::SouthEast),
+        _ => Err(Error::no_solution("the loop does not pass straight through the start")),
     }
 }
 
-fn count_interior_points(graph: &Graph<Cell, usize, petgraph::Undirected>) -> i32 {
-    if let Some(s) = graph.node_indices().find(|node| {
-        graph[*node].connections == Connections::Start
-    }) {
-        for nbor in graph.neighbors(s) {
-            let mut area: i32 = 0;
-            let mut dfs = Dfs::new(graph, nbor);
-            let mut this = s;
-            let mut n_points = 0;
-            while let Some(node) = dfs.next(graph) {
-                // Find area with shoelace algorithm
-                n_points += 1; // Keep track of boundary points
-                let x1 = graph[this].row as i32;
-                let y1 = graph[this].col as i32;
-                let x2 = graph[node].row as i32;
-                let y2 = graph[node].col as i32;
-                let n = x1*y2 - x2*y1;
-                area += n;
-                if node.index() == s.index() {
-                    // this is a closed loop
-                    area /= 2;
-                    if area < 0 {
-                        area = -area;
-                    }
-                    // Pick theorem:
-                    // Area = interior_points + boundary_points/2 - 1
-                    // We already have Area and boundary_points, we need to compute interior points.
-                    // So: 
-                    // res = Area - n_points/2 - 1
-                    return area - (n_points/2) + 1;
+pub fn infer_start(graph: &Graph<Cell, usize, petgraph::Undirected>) -> Result<Connections> {
+    start_pipe(&find_loop(graph)?)
+}
+
+/// The maze with the loop drawn in pipes and every other tile marked `I` or
+/// `O` depending on whether it is enclosed by the loop.
+pub fn render(graph: &Graph<Cell, usize, petgraph::Undirected>) -> Result<String> {
+    let path = find_loop(graph)?;
+    let rows = graph.node_weights().map(|cell| cell.row + 1).max().unwrap_or(0);
+    let cols = graph.node_weights().map(|cell| cell.col + 1).max().unwrap_or(0);
+    let mut tiles = Grid::from_elem(rows, cols, None);
+    for &(row, col) in &path {
+        tiles[(row, col)] = Some(Connections::Ground);
+    }
+    for cell in graph.node_weights() {
+        if let Some(tile) = tiles.get_mut(cell.row, cell.col).and_then(Option::as_mut) {
+            *tile = cell.connections.clone();
+        }
+    }
+    tiles[path[0]] = Some(start_pipe(&path)?);
+
+    let mut out = String::new();
+    for row in tiles.iter_rows() {
+        let mut inside = false;
+        for tile in row {
+            match tile {
+                Some(pipe) => {
+                    inside ^= pipe.north();
+                    out.push_str(&pipe.to_string());
                 }
-                this = node;
+                None => out.push(if inside { 'I' } else { 'O' }),
             }
         }
-        panic!("no loops found");
-    } else {
-        panic!("no start found");
+        out.push('\n');
     }
+    Ok(out)
+}
+
+fn find_cycle_length(graph: &Graph<Cell, usize, petgraph::Undirected>) -> Result<usize> {
+    Ok(find_loop(graph)?.len() / 2)
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day10/input2.txt").unwrap();
-    let (_, g) = parse(&input).unwrap();
-    let steps = find_cycle_length(&g);
-    println!("Day 10, Part 1: {}", steps);
+fn count_interior_points(graph: &Graph<Cell, usize, petgraph::Undirected>) -> Result<i64> {
+    let path = find_loop(graph)?;
+    let polygon = Polygon::new(path.iter().map(|&(row, col)| Point::new(col as i64, row as i64)).collect());
+    Ok(polygon.interior_points())
 }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day10/input2.txt").unwrap();
-    let (_, g) = parse(&input).unwrap();
-    let n = count_interior_points(&g);
-    println!("Day 10, Part 2: {}", n);
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+
+    type Model<'a> = Graph<Cell, usize, petgraph::Undirected>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        error::complete(input, parse(input))
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(find_cycle_length(model)?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(count_interior_points(model)?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day10/input1.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let steps = super::find_cycle_length(&g);
+        let steps = super::find_cycle_length(&g).unwrap();
         assert_eq!(steps, 8);
     }
 
@@ -351,7 +264,7 @@ mod tests {
     fn t2() {
         let input = std::fs::read_to_string("data/day10/input2.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let steps = super::find_cycle_length(&g);
+        let steps = super::find_cycle_length(&g).unwrap();
         assert_eq!(steps, 6733);
     }
 
@@ -359,17 +272,17 @@ mod tests {
     fn t3() {
         let input = std::fs::read_to_string("data/day10/input5.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let n = super::count_interior_points(&g);
+        let n = super::count_interior_points(&g).unwrap();
         assert_eq!(n, 4);
 
         let input = std::fs::read_to_string("data/day10/input4.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let n = super::count_interior_points(&g);
+        let n = super::count_interior_points(&g).unwrap();
         assert_eq!(n, 10);
 
         let input = std::fs::read_to_string("data/day10/input3.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let n = super::count_interior_points(&g);
+        let n = super::count_interior_points(&g).unwrap();
         assert_eq!(n, 8);
     }
 
@@ -377,8 +290,29 @@ mod tests {
     fn t4() {
         let input = std::fs::read_to_string("data/day10/input2.txt").unwrap();
         let (_, g) = super::parse(&input).unwrap();
-        let n = super::count_interior_points(&g);
+        let n = super::count_interior_points(&g).unwrap();
         assert_eq!(n, 435);
     }
-}
 
+    #[test]
+    fn t5() {
+        let input = std::fs::read_to_string("data/day10/input1.txt").unwrap();
+        let (_, g) = super::parse(&input).unwrap();
+        let path = super::find_loop(&g).unwrap();
+        assert_eq!(path[0], (2, 0));
+        assert_eq!(path.len(), 16);
+        assert_eq!(super::infer_start(&g).unwrap(), super::Connections::SouthEast);
+        assert_eq!(super::render(&g).unwrap(), "OO┌┐O\nO┌┘│O\n┌┘I└┐\n│┌──┘\n└┘OOO\n");
+
+        for (n, interior) in [(3, 8), (4, 10), (5, 4)] {
+            let input = std::fs::read_to_string(format!("data/day10/input{}.txt", n)).unwrap();
+            let (_, g) = super::parse(&input).unwrap();
+            let rendered = super::render(&g).unwrap();
+            assert_eq!(rendered.matches('I').count(), interior, "{}", rendered);
+        }
+
+        let (_, g) = super::parse("S7\n||\n||\nLJ").unwrap();
+        assert_eq!(super::find_cycle_length(&g).unwrap(), 4);
+        assert_eq!(super::render(&g).unwrap(), "┌┐\n││\n││\n└┘\n");
+    }
+}
@@ -1,279 +1,239 @@
-use transpose::transpose;
+use std::collections::BTreeSet;
 use std::fmt;
-
-struct Universe {
-    data: Vec<u8>,
-    r: usize,
-    c: usize
-}
-
-struct Universe2 {
-    data: Vec<u8>,
-    r: usize,
-    c: usize,
-    row_costs: Vec<usize>,
-    col_costs: Vec<usize>
+use crate::{Answer, Solution};
+use crate::error::Result;
+use crate::grid::Grid;
+
+/// The galaxies, plus how many empty rows and columns come before each row
+/// and column, so any expansion factor can be applied without rebuilding
+/// the grid.
+pub struct Universe {
+    galaxies: Vec<(usize, usize)>,
+    empty_rows_before: Vec<usize>,
+    empty_cols_before: Vec<usize>,
 }
 
 impl fmt::Debug for Universe {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}x{}\n", self.r, self.c)?;
-        for row in self.data.chunks(self.c) {
-            for c in row {
-                write!(f, "{}", *c as char)?;
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+        writeln!(f, "{}x{}", self.empty_rows_before.len(), self.empty_cols_before.len())?;
+        writeln!(f, "Galaxies: {:?}", self.galaxies)
     }
 }
 
-impl fmt::Debug for Universe2 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}x{}\n", self.r, self.c)?;
-        write!(f, "Row costs: {:?}\n", self.row_costs)?;
-        write!(f, "Col costs: {:?}\n", self.col_costs)?;
-        for row in self.data.chunks(self.c) {
-            for c in row {
-                write!(f, "{}", *c as char)?;
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+fn empty_before(empty: impl Iterator<Item = bool>) -> Vec<usize> {
+    let mut before = Vec::new();
+    let mut count = 0;
+    for is_empty in empty {
+        before.push(count);
+        count += is_empty as usize;
     }
+    before
 }
 
-impl Universe {
-    fn rotate(&mut self) {
-        let mut transposed = vec![0; self.data.len()];
-        transpose(&mut self.data, &mut transposed, self.c, self.r);
-        self.data = transposed;
-        let r = self.r;
-        self.r = self.c;
-        self.c = r;
-    }
-
-    fn expand_rows(&mut self) {
-        let mut expanded = Vec::new();
-        for row in self.data.chunks(self.c) {
-            expanded.extend_from_slice(row);
-            if !row.contains(&b'#') {
-                // expand
-                expanded.extend_from_slice(row);
-                self.r += 1;
-            }
-        }
-        self.data = expanded;
-    }
-
-    fn expand(&mut self) {
-        self.expand_rows();
-        self.rotate();
-        self.expand_rows();
-        self.rotate();
+/// Sum of `|x_i - x_j|` over all pairs, from the sorted values and their
+/// running total.
+fn pairwise_sum(mut values: Vec<u64>) -> u64 {
+    values.sort_unstable();
+    let mut prefix = 0;
+    let mut sum = 0;
+    for (i, &x) in values.iter().enumerate() {
+        sum += x * i as u64 - prefix;
+        prefix += x;
     }
+    sum
 }
 
-fn parse_input(input: &str) -> Universe {
-    let n_cols = input.split("\n").next().unwrap().len();
-    let n_rows = input.split("\n").count();
-    let v = input.as_bytes();
-    let mut data = Vec::new();
-    for c in v {
-        if *c != b'\n' {
-            data.push(*c);
-        }
-    }
-    Universe {
-        data,
-        r: n_rows,
-        c: n_cols
-    }
+/// For each value, the sum of its distances to all the others.
+fn totals(values: &[u64]) -> Vec<u64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by_key(|&i| values[i]);
+    let total: u64 = values.iter().sum();
+    let n = values.len() as u64;
+    let mut result = vec![0; values.len()];
+    let mut prefix = 0;
+    for (rank, &i) in order.iter().enumerate() {
+        let (x, rank) = (values[i], rank as u64);
+        let suffix = total - prefix - x;
+        result[i] = x * rank - prefix + suffix - x * (n - 1 - rank);
+        prefix += x;
+    }
+    result
 }
 
-fn parse_input2(input: &str, extra_cost: usize) -> Universe2 {
-    let n_cols = input.split("\n").next().unwrap().len();
-    let n_rows = input.split("\n").count();
-    let v = input.as_bytes();
-    let mut data = Vec::new();
-    for c in v {
-        if *c != b'\n' {
-            data.push(*c);
+impl Universe {
+    /// Galaxy coordinates once every empty row and column counts `factor` times.
+    pub fn positions(&self, factor: u64) -> Vec<(u64, u64)> {
+        let expand = |i: usize, empty: usize| (i - empty) as u64 + empty as u64 * factor;
+        self.galaxies
+            .iter()
+            .map(|&(row, col)| (expand(row, self.empty_rows_before[row]), expand(col, self.empty_cols_before[col])))
+            .collect()
+    }
+
+    pub fn total_distance(&self, factor: u64) -> u64 {
+        let positions = self.positions(factor);
+        pairwise_sum(positions.iter().map(|p| p.0).collect()) + pairwise_sum(positions.iter().map(|p| p.1).collect())
+    }
+
+    /// For each galaxy, the sum of its distances to every other galaxy.
+    pub fn distance_totals(&self, factor: u64) -> Vec<u64> {
+        let positions = self.positions(factor);
+        let rows = totals(&positions.iter().map(|p| p.0).collect::<Vec<_>>());
+        let cols = totals(&positions.iter().map(|p| p.1).collect::<Vec<_>>());
+        rows.into_iter().zip(cols).map(|(r, c)| r + c).collect()
+    }
+
+    /// The two closest galaxies (as indices in reading order) and their
+    /// distance. Sweeps the galaxies by row, keeping only those less than the
+    /// best distance behind ordered by column, so each galaxy is compared with
+    /// the few that fit in a box around it: O(n log n) overall.
+    pub fn closest_pair(&self, factor: u64) -> Option<(usize, usize, u64)> {
+        let positions = self.positions(factor);
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_unstable_by_key(|&i| positions[i]);
+        let mut active = BTreeSet::new();
+        let mut tail = 0;
+        let mut best: Option<(usize, usize, u64)> = None;
+        for &i in &order {
+            let (row, col) = positions[i];
+            let d = best.map_or(u64::MAX, |(_, _, d)| d);
+            while positions[order[tail]].0.saturating_add(d) <= row {
+                active.remove(&(positions[order[tail]].1, order[tail]));
+                tail += 1;
+            }
+            for &(_, j) in active.range((col.saturating_sub(d), 0)..=(col.saturating_add(d), usize::MAX)) {
+                let distance = row - positions[j].0 + col.abs_diff(positions[j].1);
+                if best.is_none_or(|(_, _, best)| distance < best) {
+                    best = Some((i.min(j), i.max(j), distance));
+                }
+            }
+            if best.is_some_and(|(_, _, d)| d == 0) {
+                break;
+            }
+            active.insert((col, i));
         }
+        best
     }
 
-    let mut row_costs = vec![0; n_rows];
-    let mut col_costs = vec![0; n_cols];
-    let mut current_row = 0;
-    for row in data.chunks(n_cols) {
-        if row.contains(&b'#') {
-            row_costs[current_row] = 1;
-        } else {
-            row_costs[current_row] = extra_cost;
+    /// The two farthest galaxies and their distance. The Manhattan distance is
+    /// the largest spread of either `row + col` or `row - col`.
+    pub fn farthest_pair(&self, factor: u64) -> Option<(usize, usize, u64)> {
+        let positions = self.positions(factor);
+        if positions.len() < 2 {
+            return None;
         }
-        current_row += 1;
+        let spread = |key: &dyn Fn(&(u64, u64)) -> i128| {
+            let by_key = |i: &usize| key(&positions[*i]);
+            let min = (0..positions.len()).min_by_key(by_key)?;
+            let max = (0..positions.len()).max_by_key(by_key)?;
+            Some((min.min(max), min.max(max), (key(&positions[max]) - key(&positions[min])) as u64))
+        };
+        let sum = spread(&|p| p.0 as i128 + p.1 as i128)?;
+        let difference = spread(&|p| p.0 as i128 - p.1 as i128)?;
+        Some(if sum.2 >= difference.2 { sum } else { difference })
     }
+}
 
-    let mut current_col = 0;
-    let mut transposed = vec![0; data.len()];
-    transpose(&mut data, &mut transposed, n_cols, n_rows);
-    for col in transposed.chunks(n_rows) {
-        if col.contains(&b'#') {
-            col_costs[current_col] = 1;
-        } else {
-            col_costs[current_col] = extra_cost;
+fn parse_input(input: &str) -> Result<Universe> {
+    let grid = Grid::parse_with(input, |c| matches!(c, '.' | '#').then_some(c))?;
+    let mut galaxies = Vec::new();
+    for ((row, col), c) in grid.indexed_iter() {
+        if *c == '#' {
+            tracing::trace!(row, col, "galaxy");
+            galaxies.push((row, col));
         }
-        current_col += 1;
     }
+    let empty_rows_before = empty_before(grid.iter_rows().map(|row| !row.contains(&'#')));
+    let empty_cols_before = empty_before(grid.iter_cols().map(|mut col| !col.any(|&c| c == '#')));
 
-    Universe2 {
-        data,
-        r: n_rows,
-        c: n_cols,
-        row_costs,
-        col_costs
-    }
+    Ok(Universe {
+        galaxies,
+        empty_rows_before,
+        empty_cols_before,
+    })
 }
 
-fn solve(universe: &Universe) -> usize {
-    let mut sum = 0;
-    let mut positions = Vec::new();
-    for (idx, c) in universe.data
-        .iter()
-        .enumerate() {
-        if *c == b'#' {
-            let row = idx / universe.c;
-            let col = idx % universe.c;
-            // println!("Galaxy: {}, {}", row, col);
-            positions.push((row, col));
-        }
-    }
+pub struct Day11;
 
-    for i in 0..positions.len() {
-        for j in i+1..positions.len() {
-            let a = positions[i];
-            let b = positions[j];
-            let d_row;
-            let d_col;
-            if a.0 > b.0 {
-                d_row = a.0 - b.0;
-            } else {
-                d_row = b.0 - a.0;
-            }
-            if a.1 > b.1 {
-                d_col = a.1 - b.1;
-            } else {
-                d_col = b.1 - a.1;
-            }
-            let d = d_row + d_col;
-            // println!("Distance between {:?} and {:?} is {}", a, b, d);
-            sum += d;
-        }
-    }
+impl Solution for Day11 {
+    const DAY: u32 = 11;
 
-    sum
-}
+    type Model<'a> = Universe;
 
-fn solve2(universe: &Universe2) -> usize {
-    let mut sum = 0;
-    let mut positions = Vec::new();
-    for (idx, c) in universe.data
-        .iter()
-        .enumerate() {
-        if *c == b'#' {
-            let row = idx / universe.c;
-            let col = idx % universe.c;
-            // println!("Galaxy: {}, {}", row, col);
-            positions.push((row, col));
-        }
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse_input(input)
     }
 
-    for i in 0..positions.len() {
-        for j in i+1..positions.len() {
-            let a = positions[i];
-            let b = positions[j];
-            let mut d_row = 0;
-            let mut d_col = 0;
-            // compute row costs
-            if a.0 < b.0 {
-                for cost in universe.row_costs[a.0..b.0].iter() {
-                    d_row += cost;
-                }
-            } else {
-                for cost in universe.row_costs[b.0..a.0].iter() {
-                    d_row += cost;
-                }
-            }
-            // compute col costs
-            if a.1 < b.1 {
-                for cost in universe.col_costs[a.1..b.1].iter() {
-                    d_col += cost;
-                }
-            } else {
-                for cost in universe.col_costs[b.1..a.1].iter() {
-                    d_col += cost;
-                }
-            }
-            let d = d_row + d_col;
-            // println!("Distance between {:?} and {:?} is {}", a, b, d);
-            sum += d;
-        }
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(model.total_distance(2).into())
     }
 
-    sum
-}
-
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day11/input2.txt").unwrap();
-    let mut universe = parse_input(&input);
-    universe.expand();
-    let res = solve(&universe);
-    println!("Day 11, Part 1: {}", res);
-}
-
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day11/input2.txt").unwrap();
-    let universe = parse_input2(&input, 1000000);
-    let res = solve2(&universe);
-    println!("Day 11, Part 2: {}", res);
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(model.total_distance(1000000).into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day11/input1.txt").unwrap();
-        let mut universe = super::parse_input(&input);
-        universe.expand();
-        let res = super::solve(&universe);
-        assert_eq!(res, 374);
+        let universe = super::parse_input(&input).unwrap();
+        assert_eq!(universe.total_distance(2), 374);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day11/input2.txt").unwrap();
-        let mut universe = super::parse_input(&input);
-        universe.expand();
-        let res = super::solve(&universe);
-        assert_eq!(res, 9521550);
+        let universe = super::parse_input(&input).unwrap();
+        assert_eq!(universe.total_distance(2), 9521550);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day11/input1.txt").unwrap();
-        let universe = super::parse_input2(&input, 10);
-        let res = super::solve2(&universe);
-        assert_eq!(res, 1030);
-
-        let universe = super::parse_input2(&input, 100);
-        let res = super::solve2(&universe);
-        assert_eq!(res, 8410);
+        let universe = super::parse_input(&input).unwrap();
+        assert_eq!(universe.total_distance(10), 1030);
+        assert_eq!(universe.total_distance(100), 8410);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day11/input2.txt").unwrap();
-        let universe = super::parse_input2(&input, 1000000);
-        let res = super::solve2(&universe);
-        assert_eq!(res, 298932923702);
+        let universe = super::parse_input(&input).unwrap();
+        assert_eq!(universe.total_distance(1000000), 298932923702);
+    }
+
+    #[test]
+    fn t5() {
+        let input = std::fs::read_to_string("data/day11/input2.txt").unwrap();
+        let universe = super::parse_input(&input).unwrap();
+        for factor in [1, 2, 1_000_000_000] {
+            let positions = universe.positions(factor);
+            let distance = |i: usize, j: usize| positions[i].0.abs_diff(positions[j].0) + positions[i].1.abs_diff(positions[j].1);
+            let pairs: Vec<(usize, usize)> = (0..positions.len()).flat_map(|i| (i + 1..positions.len()).map(move |j| (i, j))).collect();
+
+            assert_eq!(universe.total_distance(factor), pairs.iter().map(|&(i, j)| distance(i, j)).sum::<u64>());
+            let (i, j, d) = universe.closest_pair(factor).unwrap();
+            assert_eq!((d, distance(i, j)), (pairs.iter().map(|&(i, j)| distance(i, j)).min().unwrap(), d));
+            let (i, j, d) = universe.farthest_pair(factor).unwrap();
+            assert_eq!((d, distance(i, j)), (pairs.iter().map(|&(i, j)| distance(i, j)).max().unwrap(), d));
+            let totals = universe.distance_totals(factor);
+            assert_eq!(totals[7], (0..positions.len()).map(|j| distance(7, j)).sum::<u64>());
+            assert_eq!(totals.iter().sum::<u64>(), 2 * universe.total_distance(factor));
+        }
+    }
+
+    #[test]
+    fn t6() {
+        let universe = super::parse_input("#..\n...\n").unwrap();
+        assert_eq!(universe.closest_pair(2), None);
+        assert_eq!(universe.farthest_pair(2), None);
+
+        let universe = super::parse_input(&format!("{}\n", "#".repeat(2000))).unwrap();
+        assert_eq!(universe.closest_pair(2), Some((0, 1, 1)));
+        assert_eq!(universe.farthest_pair(2), Some((0, 1999, 1999)));
+        let universe = super::parse_input("#..\n..#\n#..\n").unwrap();
+        assert_eq!(universe.closest_pair(1), Some((0, 2, 2)));
     }
 }
@@ -1,216 +1,433 @@
-use std::collections::HashMap;
+use rand::Rng;
+use rayon::prelude::*;
+use std::str::FromStr;
+use crate::{Answer, Solution};
+use crate::error::{Error, Result};
 
-type Cache = HashMap::<(String, Vec<usize>), usize>;
+/// How many copies of each record part 2 unfolds, unless told otherwise.
+pub const UNFOLD: usize = 5;
 
-#[derive(Debug, Default)]
-struct Record {
-    seed: String,
+#[derive(Debug, Default, Clone)]
+pub struct Record {
+    springs: String,
     groups: Vec<usize>,
 }
 
-fn parse(input: &str) -> Vec<Record> {
-    let mut records = Vec::new();
-    for line in input.lines() {
-        let mut v = line.split(" ");
-        let seed = v.next().expect("should have seed");
-        let groups = v.next().expect("should have groups");
-        let groups = groups.split(",").map(|s| s.parse::<usize>().expect("should be a number")).collect::<Vec<_>>();
-        records.push(Record {
-            seed: seed.to_string(),
-            groups,
-        });
-    }
-    records
+pub struct Records {
+    records: Vec<Record>,
+    unfold: usize,
 }
 
-fn parse2(input: &str) -> Vec<Record> {
-    let mut records = Vec::new();
-    for line in input.lines() {
-        let mut v = line.split(" ");
-        let seed = v.next().expect("should have seed");
-        let groups = v.next().expect("should have groups");
-        let groups = format!("{},{},{},{},{}", groups, groups, groups, groups, groups);
-        let groups = groups.split(",").map(|s| s.parse::<usize>().expect("should be a number")).collect::<Vec<_>>();
+fn overflow() -> Error {
+    Error::no_solution("the number of arrangements does not fit in 64 bits")
+}
 
-        let seed = format!("{}?{}?{}?{}?{}", seed, seed, seed, seed, seed);
+/// Counts saturate rather than fail: a state that can't reach the end may
+/// overflow harmlessly, while any overflow on the way to a real arrangement
+/// saturates the total too, which `exact` then rejects. That way the counter
+/// and the completions table fail on exactly the same records.
+fn add(count: &mut u64, ways: u64) {
+    *count = count.saturating_add(ways);
+}
 
-        records.push(Record {
-            seed: seed.to_string(),
-            groups,
-        });
+fn exact(total: u64) -> Result<u64> {
+    if total == u64::MAX {
+        Err(overflow())
+    } else {
+        Ok(total)
     }
-    records
 }
 
-fn solve(records: &mut Vec<Record>) -> usize {
-    records
-    // .par_iter_mut()
-    .iter_mut()
-    .map(|record|{
-        let mut memo = Cache::new();
-        recurse(&record.seed, &record.groups, &mut memo)
-    })
-    .sum()
-}
+impl Record {
+    /// A record of `springs`, each '.', '#' or '?', with the given groups.
+    pub fn new(springs: &str, groups: Vec<usize>) -> Result<Self> {
+        if let Some(c) = springs.chars().find(|c| !matches!(c, '.' | '#' | '?')) {
+            return Err(Error::invalid(format!("unexpected spring '{}'", c)));
+        }
+        Ok(Record { springs: springs.to_string(), groups })
+    }
 
-fn on_dot(
-    seed: &str,
-    groups: &[usize],
-    cache: &mut Cache,
-) -> usize {
-    if seed.len() >= 1 {
-        let res = recurse(&seed[1..], groups, cache);
-        cache.insert((seed[1..].to_owned(), groups.to_vec()), res);
-        return res;
+    /// `factor` copies of the springs joined by `?`, with the groups repeated
+    /// to match.
+    pub fn unfold(&self, factor: usize) -> Record {
+        Record {
+            springs: vec![self.springs.as_str(); factor].join("?"),
+            groups: self.groups.repeat(factor),
+        }
     }
 
-    if groups.len() == 0 {
-        cache.insert((seed.to_owned(), groups.to_vec()), 1);
-        return 1;
+    pub fn springs(&self) -> &str {
+        &self.springs
     }
 
-    cache.insert((seed.to_owned(), groups.to_vec()), 0);
-    return 0;
-}
+    pub fn groups(&self) -> &[usize] {
+        &self.groups
+    }
+
+    pub fn arrangements(&self) -> Result<u64> {
+        self.arrangements_with(&mut Vec::new())
+    }
+
+    /// Counts arrangements one spring at a time over (groups done, length of
+    /// the run in progress) states, reusing `table` for the two layers.
+    fn arrangements_with(&self, table: &mut Vec<u64>) -> Result<u64> {
+        let groups = &self.groups;
+        let width = groups.iter().max().map_or(1, |&max| max + 1);
+        let layer = (groups.len() + 1) * width;
+        table.clear();
+        table.resize(2 * layer, 0);
+        let (mut current, mut next) = table.split_at_mut(layer);
+        current[0] = 1;
+
+        for &spring in self.springs.as_bytes() {
+            next.fill(0);
+            for done in 0..=groups.len() {
+                for run in 0..width {
+                    let ways = current[done * width + run];
+                    if ways == 0 {
+                        continue;
+                    }
+                    if spring != b'#' {
+                        if run == 0 {
+                            add(&mut next[done * width], ways);
+                        } else if run == groups[done] {
+                            add(&mut next[(done + 1) * width], ways);
+                        }
+                    }
+                    if spring != b'.' && done < groups.len() && run < groups[done] {
+                        add(&mut next[done * width + run + 1], ways);
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        let finished = current[groups.len() * width];
+        let closing = groups.last().map_or(0, |&last| current[(groups.len() - 1) * width + last]);
+        exact(finished.saturating_add(closing))
+    }
 
-fn on_hash(
-    seed: &str,
-    groups: &[usize],
-    cache: &mut Cache,
-) -> usize {
+    /// Where `(done, run)` goes on reading `spring`, if anywhere: `done` groups
+    /// are complete and the current run of `#` has length `run`.
+    fn step(&self, (done, run): (usize, usize), spring: u8) -> Option<(usize, usize)> {
+        match spring {
+            b'.' if run == 0 => Some((done, 0)),
+            b'.' if done < self.groups.len() && run == self.groups[done] => Some((done + 1, 0)),
+            b'#' if done < self.groups.len() && run < self.groups[done] => Some((done, run + 1)),
+            _ => None,
+        }
+    }
+
+    fn accepts(&self, (done, run): (usize, usize)) -> bool {
+        (done == self.groups.len() && run == 0) || (done + 1 == self.groups.len() && run == self.groups[done])
+    }
+
+    fn choices(&self, position: usize) -> &'static [u8] {
+        match self.springs.as_bytes()[position] {
+            b'.' => b".",
+            b'#' => b"#",
+            _ => b".#",
+        }
+    }
+
+    /// How many ways each state can be completed from each position.
+    pub fn completions(&self) -> Result<Completions<'_>> {
+        let width = self.groups.iter().max().map_or(1, |&max| max + 1);
+        let layer = (self.groups.len() + 1) * width;
+        let n = self.springs.len();
+        let mut counts = vec![0; (n + 1) * layer];
+        for done in 0..=self.groups.len() {
+            for run in 0..width {
+                counts[n * layer + done * width + run] = self.accepts((done, run)) as u64;
+            }
+        }
+        for position in (0..n).rev() {
+            for done in 0..=self.groups.len() {
+                for run in 0..width {
+                    let mut ways = 0;
+                    for next in self.choices(position).iter().filter_map(|&spring| self.step((done, run), spring)) {
+                        add(&mut ways, counts[(position + 1) * layer + next.0 * width + next.1]);
+                    }
+                    counts[position * layer + done * width + run] = ways;
+                }
+            }
+        }
+        exact(counts[0])?;
+        Ok(Completions { record: self, width, layer, counts })
+    }
 
-    // # ()
-    if groups.len() == 0 {
-        cache.insert((seed.to_owned(), groups.to_vec()), 0);
-        return 0;
+    /// Every arrangement, produced lazily; dead ends are never explored.
+    pub fn enumerate(&self) -> Result<Arrangements<'_>> {
+        let completions = self.completions()?;
+        let stack = if completions.total() > 0 { vec![(0, (0, 0), Vec::new())] } else { Vec::new() };
+        Ok(Arrangements { completions, stack })
     }
 
-    // ## (4)
-    if groups.iter().sum::<usize>() > seed.len() {
-        // no way to fit a group here
-        cache.insert((seed.to_owned(), groups.to_vec()), 0);
-        return 0;
+    /// Checks that `arrangement` fills in this record and matches its groups.
+    pub fn check(&self, arrangement: &str) -> Result<()> {
+        for (i, (filled, spring)) in arrangement.bytes().zip(self.springs.bytes()).enumerate() {
+            if filled != b'.' && filled != b'#' {
+                return Err(Error::invalid(format!("spring {} is '{}', expected '.' or '#'", i + 1, filled as char)));
+            }
+            if spring != b'?' && filled != spring {
+                return Err(Error::invalid(format!("spring {} is '{}', but the record has '{}'", i + 1, filled as char, spring as char)));
+            }
+        }
+        if arrangement.len() != self.springs.len() {
+            return Err(Error::invalid(format!("{} springs, expected {}", arrangement.len(), self.springs.len())));
+        }
+        let groups: Vec<usize> = arrangement.split('.').filter(|run| !run.is_empty()).map(str::len).collect();
+        if groups != self.groups {
+            return Err(Error::invalid(format!("groups are {:?}, expected {:?}", groups, self.groups)));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Record {
+    type Err = Error;
+
+    /// A single line of the puzzle input, like `???.### 1,1,3`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (springs, groups) = parse_line(0, s)?;
+        Record::new(springs, groups)
     }
+}
+
+pub struct Completions<'a> {
+    record: &'a Record,
+    width: usize,
+    layer: usize,
+    counts: Vec<u64>,
+}
 
-    // ##.# (4)
-    if seed[0..groups[0]].chars().into_iter().any(|c| c == '.') {
-        // There is a dot in the group, no way to fit
-        cache.insert((seed.to_owned(), groups.to_vec()), 0);
-        return 0;
+impl Completions<'_> {
+    fn count(&self, position: usize, (done, run): (usize, usize)) -> u64 {
+        self.counts[position * self.layer + done * self.width + run]
     }
 
-    // #### (3)
-    if seed.len() > groups[0] && seed.chars().nth(groups[0]).unwrap()  == '#' {
-        // Group is not closed, invalid config
-        cache.insert((seed.to_owned(), groups.to_vec()), 0);
-        return 0;
+    pub fn total(&self) -> u64 {
+        self.count(0, (0, 0))
     }
 
-    // ## (3)
-    if groups.len() == 1 {
-        if seed.chars().count() < groups[0] {
-            cache.insert((seed.to_owned(), groups.to_vec()), 0);
-            return 0;
+    /// One arrangement drawn uniformly: each `?` is decided with odds
+    /// proportional to the number of arrangements either choice leaves.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+        if self.total() == 0 {
+            return None;
         }
+        let mut state = (0, 0);
+        let mut arrangement = String::with_capacity(self.record.springs.len());
+        for position in 0..self.record.springs.len() {
+            let options: Vec<(u8, (usize, usize), u64)> = self
+                .record
+                .choices(position)
+                .iter()
+                .filter_map(|&spring| self.record.step(state, spring).map(|next| (spring, next, self.count(position + 1, next))))
+                .collect();
+            let mut pick = rng.gen_range(0..options.iter().map(|&(_, _, n)| n).sum::<u64>());
+            for (spring, next, n) in options {
+                if pick < n {
+                    arrangement.push(spring as char);
+                    state = next;
+                    break;
+                }
+                pick -= n;
+            }
+        }
+        Some(arrangement)
     }
+}
 
-    // ###.## (3, 2)
-    if seed.len() > groups[0] {
-        let res = recurse(&seed[groups[0]+1..], &groups[1..], cache);
-        cache.insert((seed[groups[0]+1..].to_owned(), groups[1..].to_vec()), res);
-        return res;
-    }
-
-    // ### (3)
-    cache.insert((seed.to_owned(), groups.to_vec()), 1);
-    return 1
-}
-
-fn recurse(
-    seed: &str,
-    groups: &[usize],
-    cache: &mut Cache,
-) -> usize {
-
-    let k = (seed.to_owned(), groups.to_vec());
-    if cache.contains_key(&k) {
-        return cache.get(&k).unwrap().clone();
-    }
-
-    match seed.chars().next() {
-        Some('.') => {
-            return on_dot(seed, groups, cache);
-        },
-        Some('#') => {
-            return on_hash(seed, groups, cache);
-        },
-        Some('?') => {
-            return
-                // consider this a '.': 
-                on_dot(seed, groups, cache) + 
-                // consider this a '#':
-                on_hash(seed, groups, cache);
-        },
-        None => {
-            if groups.len() == 0 {
-                return 1;
+pub struct Arrangements<'a> {
+    completions: Completions<'a>,
+    stack: Vec<(usize, (usize, usize), Vec<u8>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let record = self.completions.record;
+        while let Some((position, state, prefix)) = self.stack.pop() {
+            if position == record.springs.len() {
+                return Some(String::from_utf8(prefix).unwrap());
+            }
+            for &spring in record.choices(position).iter().rev() {
+                if let Some(next) = record.step(state, spring).filter(|&next| self.completions.count(position + 1, next) > 0) {
+                    let mut prefix = prefix.clone();
+                    prefix.push(spring);
+                    self.stack.push((position + 1, next, prefix));
+                }
             }
-            return 0;
         }
-        _ => panic!("Invalid symbol"),
+        None
+    }
+}
+
+impl Records {
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Makes part 2 unfold every record into `factor` copies.
+    pub fn with_unfold(self, factor: usize) -> Self {
+        Records { unfold: factor, ..self }
+    }
+
+    fn unfolded(&self) -> Vec<Record> {
+        self.records.iter().map(|record| record.unfold(self.unfold)).collect()
     }
 }
 
-pub fn solve1() -> usize {
-    let input = std::fs::read_to_string("data/day12/input2.txt").unwrap();
-    let mut records = parse(&input);
-    solve(&mut records)
+fn parse_line(row: usize, line: &str) -> Result<(&str, Vec<usize>)> {
+    let (seed, groups) = line.split_once(' ').ok_or_else(|| {
+        Error::parse(row + 1, line.len() + 1, "expected groups")
+    })?;
+    if let Some(col) = seed.find(|c| c != '.' && c != '#' && c != '?') {
+        return Err(Error::parse(row + 1, col + 1, "expected '.', '#' or '?'"));
+    }
+    let mut col = seed.len() + 1;
+    let mut v = Vec::new();
+    for group in groups.split(',') {
+        let n = group.parse::<usize>().map_err(|_| {
+            Error::parse(row + 1, col + 1, "expected a number")
+        })?;
+        v.push(n);
+        col += group.len() + 1;
+    }
+    Ok((seed, v))
 }
 
-pub fn solve2() -> usize {
-    let input = std::fs::read_to_string("data/day12/input2.txt").unwrap();
-    let mut records = parse2(&input);
-    solve(&mut records)
+fn parse(input: &str) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for (row, line) in input.lines().enumerate() {
+        let (seed, groups) = parse_line(row, line)?;
+        records.push(Record::new(seed, groups)?);
+    }
+    Ok(records)
 }
 
-pub fn part1() {
-    println!("Day 12, Part 1: {}", solve1());
+pub fn total_arrangements(records: &[Record]) -> Result<u64> {
+    records
+        .par_iter()
+        .map_init(Vec::new, |table, record| record.arrangements_with(table))
+        .try_reduce(|| 0, |a, b| a.checked_add(b).ok_or_else(overflow))
 }
 
-pub fn part2() {
-    println!("Day 12, Part 2: {}", solve2());
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = 12;
+
+    type Model<'a> = Records;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        Ok(Records { records: parse(input)?, unfold: UNFOLD })
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(total_arrangements(&model.records)?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(total_arrangements(&model.unfolded())?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day12/input1.txt").unwrap();
-        let mut records = super::parse(&input);
-        let res = super::solve(&mut records);
+        let records = super::parse(&input).unwrap();
+        let res = super::total_arrangements(&records).unwrap();
         assert_eq!(res, 21);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day12/input2.txt").unwrap();
-        let mut records = super::parse(&input);
-        let res = super::solve(&mut records);
+        let records = super::parse(&input).unwrap();
+        let res = super::total_arrangements(&records).unwrap();
         assert_eq!(res, 7541);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day12/input1.txt").unwrap();
-        let mut records = super::parse2(&input);
-        let res = super::solve(&mut records);
+        let records: Vec<_> = super::parse(&input).unwrap().iter().map(|r| r.unfold(5)).collect();
+        let res = super::total_arrangements(&records).unwrap();
         assert_eq!(res, 525152);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day12/input2.txt").unwrap();
-        let mut records = super::parse2(&input);
-        let res = super::solve(&mut records);
+        let records: Vec<_> = super::parse(&input).unwrap().iter().map(|r| r.unfold(5)).collect();
+        let res = super::total_arrangements(&records).unwrap();
         assert_eq!(res, 17485169859432);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn t5() {
+        let record = |springs: &str, groups: &[usize]| super::Record { springs: springs.to_string(), groups: groups.to_vec() };
+        assert_eq!(record("?###????????", &[3, 2, 1]).arrangements().unwrap(), 10);
+        assert_eq!(record("?###????????", &[3, 2, 1]).unfold(5).arrangements().unwrap(), 506250);
+        assert_eq!(record("???", &[]).arrangements().unwrap(), 1);
+        assert_eq!(record("#", &[]).arrangements().unwrap(), 0);
+        assert_eq!(record("", &[1]).arrangements().unwrap(), 0);
+        assert_eq!(record("#.#", &[1, 1]).unfold(1).arrangements().unwrap(), 1);
+        assert_eq!(record("??", &[1]).unfold(0).arrangements().unwrap(), 1);
+        assert!(record(&"?".repeat(200), &[1; 50]).arrangements().is_err());
+        assert!(record(&"?".repeat(200), &[1; 50]).completions().is_err());
+        assert!(record(&"?".repeat(200), &[1; 50]).enumerate().is_err());
+
+        use crate::{Answer, Solution};
+        let input = std::fs::read_to_string("data/day12/input1.txt").unwrap();
+        let records = super::Day12::parse(&input).unwrap().with_unfold(2);
+        let expected: u64 = records.records().iter().map(|r| r.unfold(2).arrangements().unwrap()).sum();
+        assert_eq!(super::Day12::part2(&records).unwrap(), Answer::from(expected));
+    }
+
+    #[test]
+    fn t6() {
+        use rand::SeedableRng;
+        let record = |springs: &str, groups: &[usize]| super::Record { springs: springs.to_string(), groups: groups.to_vec() };
+
+        assert_eq!(record("???.###", &[1, 1, 3]).enumerate().unwrap().collect::<Vec<_>>(), ["#.#.###"]);
+        let hard = record("?###????????", &[3, 2, 1]);
+        let all: Vec<String> = hard.enumerate().unwrap().collect();
+        assert_eq!(all.len(), 10);
+        assert!(all.iter().all(|a| hard.check(a).is_ok()));
+        assert_eq!(all.iter().collect::<std::collections::HashSet<_>>().len(), 10);
+        assert_eq!(hard.unfold(2).enumerate().unwrap().count() as u64, hard.unfold(2).arrangements().unwrap());
+        assert_eq!(record("#", &[2]).enumerate().unwrap().next(), None);
+
+        assert!(hard.check(".###.##.#...").is_ok());
+        assert_eq!(hard.check(".###.#?.#...").unwrap_err().to_string(), "invalid: spring 7 is '?', expected '.' or '#'");
+        assert_eq!(hard.check(".###.##.#..").unwrap_err().to_string(), "invalid: 11 springs, expected 12");
+        assert_eq!(hard.check(".##..##.#...").unwrap_err().to_string(), "invalid: spring 4 is '.', but the record has '#'");
+        assert_eq!(hard.check(".###.##.##..").unwrap_err().to_string(), "invalid: groups are [3, 2, 2], expected [3, 2, 1]");
+
+        let parsed: super::Record = "?###???????? 3,2,1".parse().unwrap();
+        assert_eq!(parsed.arrangements().unwrap(), 10);
+        assert_eq!(super::Record::new("?##", vec![3]).unwrap().enumerate().unwrap().collect::<Vec<_>>(), ["###"]);
+        assert!(super::Record::new("?x#", vec![1]).is_err());
+        assert!("?## 3,x".parse::<super::Record>().is_err());
+
+        let small = record(".??..??...?##.", &[1, 1, 3]);
+        let completions = small.completions().unwrap();
+        assert_eq!(completions.total(), 4);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(12);
+        let mut seen = std::collections::HashMap::new();
+        for _ in 0..4000 {
+            let arrangement = completions.sample(&mut rng).unwrap();
+            assert!(small.check(&arrangement).is_ok());
+            *seen.entry(arrangement).or_insert(0) += 1;
+        }
+        assert_eq!(seen.len(), 4);
+        assert!(seen.values().all(|&n| (800..1200).contains(&n)), "{:?}", seen);
+        assert_eq!(record("#", &[2]).completions().unwrap().sample(&mut rng), None);
+    }
+}
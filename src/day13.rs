@@ -1,225 +1,209 @@
-use grid::Grid;
-use itertools::Itertools;
+use crate::{Answer, Solution};
+use crate::error::{Error, Result};
+use crate::grid::Grid;
 
-fn parse(input: &str) -> Vec<Grid<char>> {
+fn parse(input: &str) -> Result<Vec<Grid<char>>> {
+    let lines: Vec<&str> = input.lines().collect();
     let mut grids = Vec::new();
-    let mut v: Vec<char> = Vec::new();
-    let mut columns = 0;
-    for line in input.lines() {
-        if columns == 0 {
-            columns = line.len();
-            if columns % 2 != 1 {
-                panic!("columns should be odd");
+    let mut first = 0;
+    for block in lines.split(|line| line.is_empty()) {
+        if !block.is_empty() {
+            let grid = Grid::parse_with(&block.join("\n"), |c| matches!(c, '.' | '#').then_some(c)).map_err(|e| match e {
+                Error::Parse { line, column, message } => Error::Parse { line: first + line, column, message },
+                e => e,
+            })?;
+            if grid.cols() % 2 != 1 {
+                return Err(Error::parse(first + 1, 1, "columns should be odd"));
             }
-        }
-        if line.len() == 0 {
-            // end of current grid
-            grids.push(Grid::from_vec(v.clone(), columns));
-            if grids.last().unwrap().rows() % 2 != 1 {
-                panic!("rows should be odd");
+            if grid.rows() % 2 != 1 {
+                return Err(Error::parse(first + block.len() + 1, 1, "rows should be odd"));
             }
-            v.clear();
-            columns = 0;
-        } else {
-            v.extend(line.chars());
+            grids.push(grid);
         }
+        first += block.len() + 1;
     }
-    // last grid
-    grids.push(Grid::from_vec(v.clone(), columns));
-    if grids.last().unwrap().rows() % 2 != 1 {
-        panic!("rows should be odd");
-    }
-    grids
+    Ok(grids)
 }
 
-fn _show(g: &Grid<char>) {
-    println!("");
-    print!("     ");
-    for c in 0..g.cols() {
-        print!("{}", c%10);
-    }
-    println!("");
-    for (r, i) in g.iter_rows().enumerate() {
-        print!("{r:3}| ");
-        for e in i.into_iter() {
-            print!("{e}");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Between this many rows and the rest.
+    Horizontal(usize),
+    /// Between this many columns and the rest.
+    Vertical(usize),
+}
+
+impl Axis {
+    fn summary(self) -> usize {
+        match self {
+            Axis::Horizontal(rows) => rows * 100,
+            Axis::Vertical(cols) => cols,
         }
-        println!("");
     }
 }
 
-fn solve(grids: &Vec<Grid<char>>) -> usize {
-    grids
-    .iter()
-    .map(|g| {
-        for ((r1, a), (r2, b)) in g
-        .iter_rows()
-        .enumerate()
-        .tuple_windows() {
-            let different_elements = a.zip(b).filter(|(e1, e2)| e1 != e2).count();
-            if different_elements == 0 {
-                let mut i = r1;
-                let mut j = r2;
-                let mut symmetric = true;
-                while i > 0 && j < g.rows()-1 {
-                    i -= 1;
-                    j += 1;
-                    if g.iter_rows().nth(i).unwrap().collect::<Vec<&char>>() != g.iter_rows().nth(j).unwrap().collect::<Vec<&char>>() {
-                        symmetric = false;
-                        break;
-                    }
-                }
-                if symmetric {
-                    return (r1 + 1) * 100;
-                }
-            }
-        }
+/// A reflection axis and the mismatched cells across it, each paired with its
+/// mirror image; fixing either cell of a pair removes that mismatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub smudges: Vec<Smudge>,
+}
+
+pub type Smudge = ((usize, usize), (usize, usize));
 
-        for ((c1, a), (c2, b)) in g
-        .iter_cols()
-        .enumerate()
-        .tuple_windows() {
-            let different_elements = a.zip(b).filter(|(e1, e2)| e1 != e2).count();
-            if different_elements == 0 {
-                let mut i = c1;
-                let mut j = c2;
-                let mut symmetric = true;
-                while i > 0 && j < g.cols()-1 {
-                    i -= 1;
-                    j += 1;
-                    if g.iter_cols().nth(i).unwrap().collect::<Vec<&char>>() != g.iter_cols().nth(j).unwrap().collect::<Vec<&char>>() {
-                        symmetric = false;
-                        break;
-                    }
-                }
-                if symmetric {
-                    return c1 + 1;
-                }
+/// Each line as a bitset of its '#' cells, 64 cells per word.
+fn masks<'a>(lines: impl Iterator<Item = impl Iterator<Item = &'a char>>, width: usize) -> Vec<Vec<u64>> {
+    lines
+        .map(|line| {
+            let mut words = vec![0; width.div_ceil(64)];
+            for (i, _) in line.enumerate().filter(|(_, &c)| c == '#') {
+                words[i / 64] |= 1 << (i % 64);
             }
-        }
-        
-        panic!("no symmetry found");
+            words
+        })
+        .collect()
+}
+
+/// The positions where two lines differ.
+fn differences<'a>(a: &'a [u64], b: &'a [u64]) -> impl Iterator<Item = usize> + 'a {
+    a.iter().zip(b).enumerate().flat_map(|(word, (x, y))| {
+        let diff = x ^ y;
+        (0..64).filter(move |bit| diff & 1 << bit != 0).map(move |bit| word * 64 + bit)
     })
-    .sum()
 }
 
-fn solve2(grids: &Vec<Grid<char>>) -> usize {
-    grids
-    .iter()
-    .map(|g| {
-        for ((r1, a), (r2, b)) in g
-        .iter_rows()
-        .enumerate()
-        .tuple_windows() {
-            let mut smudge_used = false;
-            let different_elements = a.zip(b).filter(|(e1, e2)| e1 != e2).count();
-            if different_elements == 1 {
-                smudge_used = true;
-            }
-            if different_elements == 0 || smudge_used {
-                let mut i = r1;
-                let mut j = r2;
-                let mut symmetric = true;
-                while i > 0 && j < g.rows()-1 {
-                    i -= 1;
-                    j += 1;
-                    let different_elements_in_row = g.iter_rows().nth(i).unwrap().zip(g.iter_rows().nth(j).unwrap()).filter(|(e1, e2)| e1 != e2).count();
-                    if different_elements_in_row == 1 && !smudge_used {
-                        smudge_used = true;
-                        continue;
-                    }
-                    if different_elements_in_row == 0 {
-                        continue;
-                    }
-                    symmetric = false;
-                    break;   
-                }
-                if symmetric && smudge_used {
-                    return (r1 + 1) * 100;
-                }
+/// Axes between `lines` with exactly `k` mismatched cells, with the (line,
+/// cell) positions of each mismatch and its mirror.
+fn axes(lines: &[Vec<u64>], k: u32) -> Vec<(usize, Vec<Smudge>)> {
+    (1..lines.len())
+        .filter_map(|axis| {
+            let pairs = (0..axis).rev().zip(axis..lines.len());
+            let mismatches: u32 = pairs
+                .clone()
+                .flat_map(|(i, j)| lines[i].iter().zip(&lines[j]).map(|(x, y)| (x ^ y).count_ones()))
+                .sum();
+            if mismatches != k {
+                return None;
             }
-        }
+            let smudges = pairs
+                .flat_map(|(i, j)| differences(&lines[i], &lines[j]).map(move |cell| ((i, cell), (j, cell))))
+                .collect();
+            Some((axis, smudges))
+        })
+        .collect()
+}
 
-        for ((c1, a), (c2, b)) in g
-        .iter_cols()
-        .enumerate()
-        .tuple_windows() {
-            let mut smudge_used = false;
-            let different_elements = a.zip(b).filter(|(e1, e2)| e1 != e2).count();
-            if different_elements == 1 {
-                smudge_used = true;
-            }
-            if different_elements == 0 || smudge_used {
-                let mut i = c1;
-                let mut j = c2;
-                let mut symmetric = true;
-                while i > 0 && j < g.cols()-1 {
-                    i -= 1;
-                    j += 1;
-                    let different_elements_in_col = g.iter_cols().nth(i).unwrap().zip(g.iter_cols().nth(j).unwrap()).filter(|(e1, e2)| e1 != e2).count();
-                    if different_elements_in_col == 1 && !smudge_used {
-                        smudge_used = true;
-                        continue;
-                    }
-                    if different_elements_in_col == 0 {
-                        continue;
-                    }
-                    symmetric = false;
-                    break;   
-                }
-                if symmetric && smudge_used {
-                    return c1 + 1;
-                }
-            }
-        }
-        
-        panic!("no symmetry found");
-    })
-    .sum()
+/// Every horizontal and vertical reflection with exactly `k` smudges.
+pub fn reflections(g: &Grid<char>, k: u32) -> Vec<Reflection> {
+    let horizontal = axes(&masks(g.iter_rows().map(|row| row.iter()), g.cols()), k)
+        .into_iter()
+        .map(|(axis, smudges)| Reflection { axis: Axis::Horizontal(axis), smudges });
+    let vertical = axes(&masks(g.iter_cols(), g.rows()), k).into_iter().map(|(axis, smudges)| Reflection {
+        axis: Axis::Vertical(axis),
+        smudges: smudges.into_iter().map(|((c1, r1), (c2, r2))| ((r1, c1), (r2, c2))).collect(),
+    });
+    horizontal.chain(vertical).collect()
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day13/input2.txt").unwrap();
-    let grids = parse(&input);
-    println!("Day 13, Part 1: {}", solve(&grids));
+fn solve(grids: &[Grid<char>], k: u32) -> Result<usize> {
+    grids
+        .iter()
+        .map(|g| {
+            reflections(g, k)
+                .first()
+                .map(|reflection| reflection.axis.summary())
+                .ok_or_else(|| Error::no_solution("no symmetry found"))
+        })
+        .sum()
 }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day13/input2.txt").unwrap();
-    let grids = parse(&input);
-    println!("Day 13, Part 2: {}", solve2(&grids));
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = 13;
+
+    type Model<'a> = Vec<Grid<char>>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(model, 0)?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(model, 1)?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day13/input1.txt").unwrap();
-        let grids = super::parse(&input);
-        let res = super::solve(&grids);
+        let grids = super::parse(&input).unwrap();
+        let res = super::solve(&grids, 0).unwrap();
         assert_eq!(405, res);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day13/input2.txt").unwrap();
-        let grids = super::parse(&input);
-        let res = super::solve(&grids);
+        let grids = super::parse(&input).unwrap();
+        let res = super::solve(&grids, 0).unwrap();
         assert_eq!(43614, res);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day13/input1.txt").unwrap();
-        let grids = super::parse(&input);
-        let res = super::solve2(&grids);
+        let grids = super::parse(&input).unwrap();
+        let res = super::solve(&grids, 1).unwrap();
         assert_eq!(400, res);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day13/input2.txt").unwrap();
-        let grids = super::parse(&input);
-        let res = super::solve2(&grids);
+        let grids = super::parse(&input).unwrap();
+        let res = super::solve(&grids, 1).unwrap();
         assert_eq!(36771, res);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn t5() {
+        use super::{Axis, Reflection};
+        let input = std::fs::read_to_string("data/day13/input1.txt").unwrap();
+        let grids = super::parse(&input).unwrap();
+        assert_eq!(super::reflections(&grids[0], 0), vec![Reflection { axis: Axis::Vertical(5), smudges: vec![] }]);
+        assert_eq!(super::reflections(&grids[0], 1), vec![Reflection { axis: Axis::Horizontal(3), smudges: vec![((0, 0), (5, 0))] }]);
+        assert_eq!(super::reflections(&grids[1], 1), vec![Reflection { axis: Axis::Horizontal(1), smudges: vec![((0, 4), (1, 4))] }]);
+        assert!(super::reflections(&grids[1], 2).iter().all(|r| r.smudges.len() == 2));
+    }
+
+    #[test]
+    fn t6() {
+        use super::{Axis, Reflection};
+        let line = |f: fn(usize) -> bool| (0..129).map(|i| if f(i) { '#' } else { '.' }).collect::<String>();
+        let (a, b, c) = (line(|i| i % 3 == 0), line(|i| i % 7 < 2), line(|i| i * i % 11 == 1));
+        let mut smudged = a.clone();
+        smudged.replace_range(100..101, if a.as_bytes()[100] == b'#' { "." } else { "#" });
+        let input = format!("{a}\n{b}\n{b}\n{a}\n{c}\n\n{a}\n{b}\n{b}\n{smudged}\n{c}\n");
+        let grids = super::parse(&input).unwrap();
+        assert!(super::reflections(&grids[0], 0).contains(&Reflection { axis: Axis::Horizontal(2), smudges: vec![] }));
+        assert!(super::reflections(&grids[1], 1).contains(&Reflection { axis: Axis::Horizontal(2), smudges: vec![((0, 100), (3, 100))] }));
+    }
+
+    #[test]
+    fn t7() {
+        let e = super::parse("#.#\n...\n#.#\n\n\u{e9}a\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 5, column 1: unexpected character '\u{e9}'");
+        let e = super::parse("#.#\n\n#.x\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 3, column 3: unexpected character 'x'");
+        let e = super::parse("#.#\n...\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 3, column 1: rows should be odd");
+        assert_eq!(super::parse("#.#\n\n.#.\n").unwrap().len(), 2);
+    }
+}
@@ -1,13 +1,12 @@
-use grid::Grid;
-
-fn parse(input: &str) -> Grid<char> {
-    let cols = input.find('\n').expect("expected at least one newline");
-    let v: Vec<char> =
-    input
-    .chars()
-    .filter(|&c| c != '\n')
-    .collect();
-    Grid::from_vec(v, cols)
+use crate::{Answer, Solution};
+use crate::cycle;
+use crate::error::Result;
+use crate::grid::Grid;
+
+const CYCLES: usize = 1000000000;
+
+fn parse(input: &str) -> Result<Grid<char>> {
+    Grid::parse_with(input, |c| matches!(c, 'O' | '#' | '.').then_some(c))
 }
 
 fn rotate_north(g: &mut Grid<char>) {
@@ -29,8 +28,8 @@ fn rotate_north(g: &mut Grid<char>) {
                         cur_row -= 1;
                     }
                     if cur_row != r {
-                        *(g.get_mut(cur_row, c).unwrap()) = 'O';
-                        *(g.get_mut(r, c).unwrap()) = '.';
+                        g[(cur_row, c)] = 'O';
+                        g[(r, c)] = '.';
                     }
                 }
             }
@@ -57,8 +56,8 @@ fn rotate_south(g: &mut Grid<char>) {
                         cur_row += 1;
                     }
                     if cur_row != r {
-                        *(g.get_mut(cur_row, c).unwrap()) = 'O';
-                        *(g.get_mut(r, c).unwrap()) = '.';
+                        g[(cur_row, c)] = 'O';
+                        g[(r, c)] = '.';
                     }
                 }
             }
@@ -85,8 +84,8 @@ fn rotate_west(g: &mut Grid<char>) {
                         cur_col -= 1;
                     }
                     if cur_col != c {
-                        *(g.get_mut(r, cur_col).unwrap()) = 'O';
-                        *(g.get_mut(r, c).unwrap()) = '.';
+                        g[(r, cur_col)] = 'O';
+                        g[(r, c)] = '.';
                     }
                 }
             }
@@ -113,8 +112,8 @@ fn rotate_east(g: &mut Grid<char>) {
                         cur_col += 1;
                     }
                     if cur_col != c {
-                        *(g.get_mut(r, cur_col).unwrap()) = 'O';
-                        *(g.get_mut(r, c).unwrap()) = '.';
+                        g[(r, cur_col)] = 'O';
+                        g[(r, c)] = '.';
                     }
                 }
             }
@@ -123,31 +122,17 @@ fn rotate_east(g: &mut Grid<char>) {
 }
 
 fn solve(g: &mut Grid<char>) -> usize {
-    let mut weight = g.cols();
     rotate_north(g);
-    g
-    .iter_rows()
-    .map(|r|{
-        let w = weight * r
-        .filter(|&&e| e == 'O')
-        .count();
-        weight -= 1;
-        w
-    })
-    .sum()
+    compute_weight(g)
 }
 
+/// Each rounded rock weighs as many rows as there are from its own row to the
+/// south edge, inclusive.
 fn compute_weight(g: &Grid<char>) -> usize {
-    let mut weight = g.cols();
     g
     .iter_rows()
-    .map(|r|{
-        let w = weight * r
-        .filter(|&&e| e == 'O')
-        .count();
-        weight -= 1;
-        w
-    })
+    .zip((1..=g.rows()).rev())
+    .map(|(r, weight)| weight * r.iter().filter(|&&e| e == 'O').count())
     .sum()
 }
 
@@ -158,70 +143,47 @@ fn tortoise(g: &mut Grid<char>) {
     rotate_east(g);
 }
 
-fn hare(g: &mut Grid<char>) {
-    tortoise(g);
-    tortoise(g);
+fn solve2(g: &mut Grid<char>) -> usize {
+    let _span = tracing::debug_span!("cycle_detection").entered();
+    let spin = |g: &Grid<char>| {
+        let mut g = g.clone();
+        tortoise(&mut g);
+        g
+    };
+    let (mu, lam) = cycle::find_cycle(g, spin);
+    tracing::debug!(mu, "cycle start found");
+    tracing::debug!(lam, "cycle length found");
+
+    *g = cycle::state_at(g, spin, (mu, lam), CYCLES);
+    compute_weight(g)
 }
 
-fn solve2(g: &mut Grid<char>) -> usize {
-    // println!("\n\n");
-    let mut t = g.clone();
-    let mut h = g.clone();
-    hare(&mut h);
-    tortoise(&mut t);
-    while t != h {
-        hare(&mut h);
-        tortoise(&mut t);
-    }
-    // println!("Cycle detected after {} iterations", i);
-    let mut mu = 0;
-    t = g.clone();
-    while t != h {
-        tortoise(&mut t);
-        tortoise(&mut h);
-        mu += 1;
-    }
-    // println!("mu = {}", mu);
+pub struct Day14;
 
-    let mut lam = 1;
-    tortoise(&mut t);
-    while t != h {
-        tortoise(&mut t);
-        lam += 1;
-    }
-    // println!("lam = {}", lam);
+impl Solution for Day14 {
+    const DAY: u32 = 14;
 
-    // perform mu iterations
-    for _ in 0..mu {
-        tortoise(g);
-    }
-    // perform 1000000000 % lam iterations
-    let n = (1000000000 - mu) % lam;
-    // println!("n = {}", n);
-    for _ in 0..n {
-        tortoise(g);
-    }
+    type Model<'a> = Grid<char>;
 
-    compute_weight(&g)
-}
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day14/input2.txt").unwrap();
-    let mut grid = parse(&input);
-    println!("Day 14, Part 1: {}", solve(&mut grid));
-}
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(&mut model.clone()).into())
+    }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day14/input2.txt").unwrap();
-    let mut grid = parse(&input);
-    println!("Day 14, Part 2: {}", solve2(&mut grid));
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve2(&mut model.clone()).into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day14/input1.txt").unwrap();
-        let mut grid = super::parse(&input);
+        let mut grid = super::parse(&input).unwrap();
         let res = super::solve(&mut grid);
         assert_eq!(136, res);
     }
@@ -229,24 +191,41 @@ mod tests {
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day14/input2.txt").unwrap();
-        let mut grid = super::parse(&input);
+        let mut grid = super::parse(&input).unwrap();
         let res = super::solve(&mut grid);
         assert_eq!(105249, res);
     }
 
     #[test]
+    #[tracing_test::traced_test]
     fn t3() {
         let input = std::fs::read_to_string("data/day14/input1.txt").unwrap();
-        let mut grid = super::parse(&input);
+        let mut grid = super::parse(&input).unwrap();
         let res = super::solve2(&mut grid);
         assert_eq!(64, res);
+        assert!(logs_contain("cycle length found lam=7"));
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day14/input2.txt").unwrap();
-        let mut grid = super::parse(&input);
+        let mut grid = super::parse(&input).unwrap();
         let res = super::solve2(&mut grid);
         assert_eq!(88680, res);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn t5() {
+        let mut grid = super::parse("O.#.\n..O.\n.O..\n").unwrap();
+        assert_eq!(super::compute_weight(&grid), 3 + 2 + 1);
+        assert_eq!(super::solve(&mut grid.clone()), 3 + 3 + 2);
+
+        let mut tall = super::parse("O.\n..\n#O\n..\n.O\n").unwrap();
+        assert_eq!(super::solve(&mut tall.clone()), 5 + 5 + 4);
+        super::tortoise(&mut tall);
+        assert_eq!(tall.to_string(), ".O\n.O\n#.\n..\n.O\n");
+        assert_eq!(super::solve2(&mut tall), 5 + 4 + 1);
+        super::tortoise(&mut grid);
+        assert_eq!(grid.to_string(), "..#.\n...O\n..OO\n");
+    }
+}
@@ -1,16 +1,30 @@
-fn parse(input: &str) -> Vec<Vec<char>> {
-    let v = input.split(',').map(|x| x.chars().collect()).collect();
-
-    v
+use crate::{Answer, Solution};
+use crate::error::{Error, Result};
+fn parse(input: &str) -> Result<Vec<Vec<char>>> {
+    let mut v = Vec::new();
+    let mut column = 1;
+    for step in input.trim_end().split(',') {
+        let valid = match step.find(['=', '-']) {
+            Some(i) if &step[i..] == "-" => i > 0,
+            Some(i) => i > 0 && step[i + 1..].parse::<usize>().is_ok(),
+            None => false,
+        };
+        if !valid {
+            return Err(Error::parse(1, column, format!("invalid step `{}`", step)));
+        }
+        v.push(step.chars().collect());
+        column += step.len() + 1;
+    }
+    Ok(v)
 }
 
-fn aoc_hash(v: &Vec<char>) -> usize {
+fn aoc_hash(v: &[char]) -> usize {
     v.iter().fold(0, |acc, &c|{
         (acc + c as usize) * 17 % 256
     })
 }
 
-fn solve(data: &Vec<Vec<char>>) -> usize {
+fn solve(data: &[Vec<char>]) -> usize {
     data
     .iter()
     .map(|block|{
@@ -39,11 +53,11 @@ struct Step {
 }
 
 
-fn get_steps(data: &Vec<Vec<char>>) -> Vec<Step>
+fn get_steps(data: &[Vec<char>]) -> Vec<Step>
 {
     let mut v = Vec::new();
     for block in data {
-        let seed: Vec<char> = block.iter().take_while(|&&c| c != '=' && c != '-').map(|c| c.clone()).collect();
+        let seed: Vec<char> = block.iter().take_while(|&&c| c != '=' && c != '-').copied().collect();
         let chest_id = aoc_hash(&seed);
         let operation = if block[seed.len()] == '=' {
             Operation::Insert
@@ -52,7 +66,7 @@ fn get_steps(data: &Vec<Vec<char>>) -> Vec<Step>
         };
         let mut focal_length = 0;
         if operation == Operation::Insert {
-            focal_length = block[seed.len()+1..].into_iter().collect::<String>().parse().unwrap();
+            focal_length = block[seed.len()+1..].iter().collect::<String>().parse().expect("focal length checked by parse");
         }
 
         v.push(Step {
@@ -68,11 +82,11 @@ fn get_steps(data: &Vec<Vec<char>>) -> Vec<Step>
     v
 }
 
-fn solve2(data: &Vec<Vec<char>>) -> usize {
+fn solve2(data: &[Vec<char>]) -> usize {
     let steps = get_steps(data);
 
     let mut chests: Vec<Vec<Chest>> = Vec::with_capacity(256);
-    chests.resize_with(256, || Vec::new());
+    chests.resize_with(256, Vec::new);
 
     
     for step in steps {
@@ -113,44 +127,53 @@ fn solve2(data: &Vec<Vec<char>>) -> usize {
    .sum()
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day15/input2.txt").unwrap();
-    let data = parse(&input);
-    println!("Day 15, Part 1: {}", solve(&data));
-}
+pub struct Day15;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day15/input2.txt").unwrap();
-    let data = parse(&input);
-    println!("Day 15, Part 2: {}", solve2(&data));
+impl Solution for Day15 {
+    const DAY: u32 = 15;
+
+    type Model<'a> = Vec<Vec<char>>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(model).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve2(model).into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day15/input1.txt").unwrap();
-        let data = super::parse(&input);
+        let data = super::parse(&input).unwrap();
         assert_eq!(1320, super::solve(&data));
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day15/input2.txt").unwrap();
-        let data = super::parse(&input);
+        let data = super::parse(&input).unwrap();
         assert_eq!(514639, super::solve(&data));
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day15/input1.txt").unwrap();
-        let data = super::parse(&input);
+        let data = super::parse(&input).unwrap();
         assert_eq!(145, super::solve2(&data));
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day15/input2.txt").unwrap();
-        let data = super::parse(&input);
+        let data = super::parse(&input).unwrap();
         assert_eq!(279470, super::solve2(&data));
     }
 }
\ No newline at end of file
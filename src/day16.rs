@@ -1,3 +1,7 @@
+use crate::{Answer, Solution};
+use crate::error::Result;
+use crate::grid::Grid;
+
 #[derive(Debug, Clone)]
 enum CellType {
     Empty,
@@ -22,10 +26,8 @@ struct Cell {
 }
 
 #[derive(Debug, Clone)]
-struct Maze {
-    rows: usize,
-    cols: usize,
-    cells: Vec<Vec<Cell>>,
+pub struct Maze {
+    cells: Grid<Cell>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,9 +50,9 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
             return;
         }
 
-        // dbg!(beam.row, beam.col, &beam.direction, beam.visited_cells.len());
+        tracing::trace!(row = beam.row, col = beam.col, direction = ?beam.direction, visited = visited_cells.len(), "beam step");
 
-        maze.cells[beam.row][beam.col].energized = true;
+        maze.cells[(beam.row, beam.col)].energized = true;
 
         visited_cells.push(VisitedCell {
             row: beam.row,
@@ -60,7 +62,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
 
         let mut new_beam = None;
 
-        match maze.cells[beam.row][beam.col].cell_type {
+        match maze.cells[(beam.row, beam.col)].cell_type {
             CellType::Empty => {
                 match beam.direction {
                     Direction::Up => {
@@ -71,7 +73,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     }
                     Direction::Down => {
-                        if beam.row < maze.rows - 1 {
+                        if beam.row < maze.cells.rows() - 1 {
                             beam.row += 1;
                         } else {
                             return;
@@ -85,7 +87,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     }
                     Direction::Right => {
-                        if beam.col < maze.cols - 1 {
+                        if beam.col < maze.cells.cols() - 1 {
                             beam.col += 1;
                         } else {
                             return;
@@ -96,7 +98,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
             CellType::ForwardSlash => {
                 match beam.direction {
                     Direction::Up => {
-                        if beam.col < maze.cols - 1 {
+                        if beam.col < maze.cells.cols() - 1 {
                             beam.col += 1;
                             beam.direction = Direction::Right;
                         } else {
@@ -112,7 +114,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     }
                     Direction::Left => {
-                        if beam.row < maze.rows - 1 {
+                        if beam.row < maze.cells.rows() - 1 {
                             beam.row += 1;
                             beam.direction = Direction::Down;
                         } else {
@@ -140,7 +142,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     }
                     Direction::Down => {
-                        if beam.col < maze.cols - 1 {
+                        if beam.col < maze.cells.cols() - 1 {
                             beam.col += 1;
                             beam.direction = Direction::Right;
                         } else {
@@ -156,7 +158,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     }
                     Direction::Right => {
-                        if beam.row < maze.rows - 1 {
+                        if beam.row < maze.cells.rows() - 1 {
                             beam.row += 1;
                             beam.direction = Direction::Down;
                         } else {
@@ -174,7 +176,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                             beam.col -= 1;
                         }
                         if let Some(x) = new_beam.as_mut() {
-                            if x.col < maze.cols - 1 {
+                            if x.col < maze.cells.cols() - 1 {
                                 x.direction = Direction::Right;
                                 x.col += 1;
                             }
@@ -188,7 +190,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     },
                     Direction::Right => {
-                        if beam.col < maze.cols - 1 {
+                        if beam.col < maze.cells.cols() - 1 {
                             beam.col += 1;
                         } else {
                             return;
@@ -205,7 +207,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                             beam.row -= 1;
                         }
                         if let Some(x) = new_beam.as_mut() {
-                            if x.row < maze.rows - 1 {
+                            if x.row < maze.cells.rows() - 1 {
                                 x.direction = Direction::Down;
                                 x.row += 1;
                             }
@@ -219,7 +221,7 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
                         }
                     },
                     Direction::Down => {
-                        if beam.row < maze.rows - 1 {
+                        if beam.row < maze.cells.rows() - 1 {
                             beam.row += 1;
                         } else {
                             return;
@@ -235,42 +237,33 @@ fn recurse(beam: &mut Beam, maze: &mut Maze, visited_cells: &mut Vec<VisitedCell
     }
 }
 
-fn parse(input: &str) -> Maze {
-    let rows = input.lines().count();
-    let cols = input.lines().next().unwrap().chars().count();
-    
-    Maze {
-        rows,
-        cols,
-        cells: input.lines().map(|line|{
-            line.chars().map(|c|{
-                Cell {
-                    cell_type: match c {
-                        '.' => CellType::Empty,
-                        '/' => CellType::ForwardSlash,
-                        '\\' => CellType::BackwardSlash,
-                        '-' => CellType::Dash,
-                        '|' => CellType::VerticalBar,
-                        _ => panic!("Unknown cell type: {}", c),
-                    },
-                    energized: false,
-                }
-            }).collect()
-        }).collect()
-    }
+fn parse(input: &str) -> Result<Maze> {
+    let cells = Grid::parse_with(input, |c| {
+        let cell_type = match c {
+            '.' => CellType::Empty,
+            '/' => CellType::ForwardSlash,
+            '\\' => CellType::BackwardSlash,
+            '-' => CellType::Dash,
+            '|' => CellType::VerticalBar,
+            _ => return None,
+        };
+        Some(Cell { cell_type, energized: false })
+    })?;
+    Ok(Maze { cells })
 }
 
 fn solve(maze: &mut Maze, beam: &mut Beam) -> usize {
+    let _span = tracing::debug_span!("beam", row = beam.row, col = beam.col, direction = ?beam.direction).entered();
     recurse(beam, maze, &mut Vec::new());
     
-    maze.cells.iter().map(|row|{
-        row.iter().filter(|cell| cell.energized).count()
-    }).sum()
+    let energized = maze.cells.iter().filter(|cell| cell.energized).count();
+    tracing::debug!(energized, "beam done");
+    energized
 }
 
 fn solve2(maze: &mut Maze) -> usize {
     let mut max = 0;
-    for row in 0..maze.rows {
+    for row in 0..maze.cells.rows() {
         let mut beam = Beam {
             row,
             col: 0,
@@ -282,7 +275,7 @@ fn solve2(maze: &mut Maze) -> usize {
         }
         let mut beam = Beam {
             row,
-            col: maze.cols - 1,
+            col: maze.cells.cols() - 1,
             direction: Direction::Left,
         };
         let n = solve(&mut maze.clone(), &mut beam);
@@ -290,7 +283,7 @@ fn solve2(maze: &mut Maze) -> usize {
             max = n;
         }
     }
-    for col in 0..maze.cols {
+    for col in 0..maze.cells.cols() {
         let mut beam = Beam {
             row: 0,
             col,
@@ -301,7 +294,7 @@ fn solve2(maze: &mut Maze) -> usize {
             max = n;
         }
         let mut beam = Beam {
-            row: maze.rows - 1,
+            row: maze.cells.rows() - 1,
             col,
             direction: Direction::Up,
         };
@@ -313,28 +306,37 @@ fn solve2(maze: &mut Maze) -> usize {
     max
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day16/input2.txt").unwrap();
-    let mut data = parse(&input);
-    let mut beam = Beam {
-        row: 0,
-        col: 0,
-        direction: Direction::Right
-    };
-    println!("Day 16, Part 1: {}", solve(&mut data, &mut beam));
-}
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u32 = 16;
+
+    type Model<'a> = Maze;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day16/input2.txt").unwrap();
-    let mut data = parse(&input);
-    println!("Day 16, Part 2: {}", solve2(&mut data));
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        let mut beam = Beam {
+            row: 0,
+            col: 0,
+            direction: Direction::Right
+        };
+        Ok(solve(&mut model.clone(), &mut beam).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve2(&mut model.clone()).into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day16/input1.txt").unwrap();
-        let mut data = super::parse(&input);
+        let mut data = super::parse(&input).unwrap();
         let mut beam = super::Beam {
             row: 0,
             col: 0,
@@ -346,7 +348,7 @@ mod tests {
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day16/input2.txt").unwrap();
-        let mut data = super::parse(&input);
+        let mut data = super::parse(&input).unwrap();
         let mut beam = super::Beam {
             row: 0,
             col: 0,
@@ -358,14 +360,14 @@ mod tests {
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day16/input1.txt").unwrap();
-        let mut data = super::parse(&input);
+        let mut data = super::parse(&input).unwrap();
         assert_eq!(51, super::solve2(&mut data));
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day16/input2.txt").unwrap();
-        let mut data = super::parse(&input);
+        let mut data = super::parse(&input).unwrap();
         assert_eq!(9064, super::solve2(&mut data));
     }
 }
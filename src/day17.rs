@@ -1,11 +1,7 @@
 use pathfinding::prelude::dijkstra;
-
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct Cost {
-    row: usize,
-    col: usize,
-    cost: usize,
-}
+use crate::{Answer, Solution};
+use crate::error::{Error, Result};
+use crate::grid::Grid;
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 struct Position {
@@ -33,23 +29,23 @@ enum Direction {
     Right,
 }
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct Visitor {
-    costs: Vec<Cost>,
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Visitor {
+    costs: Grid<usize>,
     rows: usize,
     cols: usize,
 }
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct Visitor2 {
-    costs: Vec<Cost>,
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Visitor2 {
+    costs: Grid<usize>,
     rows: usize,
     cols: usize,
 }
 
 impl Visitor {
     fn cost(&self, r: usize, c: usize) -> usize {
-        self.costs.iter().find(|x| x.row == r && x.col == c).unwrap().cost
+        self.costs[(r, c)]
     }
 
     fn successors(&self, p: &Position) -> Vec<(Position, usize)> {
@@ -172,27 +168,14 @@ impl Visitor {
                 }
             },
         }
+        tracing::trace!(row = p.row, col = p.col, direction = ?p.direction, steps_done = p.steps_done, successors = positions.len(), "expand");
         positions
     }
-
-    // fn show_path(&self, path: &Vec<Position>) {
-    //     println!("\n\n");
-    //     for x in self.costs.iter() {
-    //         if path.iter().any(|p| p.row == x.row && p.col == x.col) {
-    //             print!("#");
-    //         } else {
-    //             print!("{}", x.cost);
-    //         }
-    //         if x.col == self.cols - 1 {
-    //             println!();
-    //         }
-    //     }
-    // }
 }
 
 impl Visitor2 {
     fn cost(&self, r: usize, c: usize) -> usize {
-        self.costs.iter().find(|x| x.row == r && x.col == c).unwrap().cost
+        self.costs[(r, c)]
     }
 
     fn successors(&self, p: &Position2) -> Vec<(Position2, usize)> {
@@ -327,68 +310,39 @@ impl Visitor2 {
                 }
             },
         }
+        tracing::trace!(row = p.row, col = p.col, direction = ?p.direction, steps_done = p.steps_done, successors = positions.len(), "expand");
         positions
     }
-
-    // fn show_path(&self, path: &Vec<Position2>) {
-    //     println!("\n\n");
-    //     for x in self.costs.iter() {
-    //         if path.iter().any(|p| p.row == x.row && p.col == x.col) {
-    //             print!("#");
-    //         } else {
-    //             print!("{}", x.cost);
-    //         }
-    //         if x.col == self.cols - 1 {
-    //             println!();
-    //         }
-    //     }
-    // }
 }
 
 
-fn parse(input: &str) -> Visitor {
-    let rows = input.lines().count();
-    let cols = input.lines().next().unwrap().chars().count();
+fn parse_costs(input: &str) -> Result<(Grid<usize>, usize, usize)> {
+    let costs = Grid::parse_with(input, |c| c.to_digit(10).map(|d| d as usize))?;
+    let (rows, cols) = (costs.rows(), costs.cols());
+    Ok((costs, rows, cols))
+}
 
-    Visitor {
-        costs: input.lines().enumerate().map(|(row, line)| {
-            line.chars().enumerate().map(move |(col, c)| {
-                Cost {
-                    row,
-                    col,
-                    cost: c.to_digit(10).unwrap() as usize,
-                }
-            })
-        })
-        .flatten()
-        .collect(),
+fn parse(input: &str) -> Result<Visitor> {
+    let (costs, rows, cols) = parse_costs(input)?;
+
+    Ok(Visitor {
+        costs,
         rows,
         cols,
-    }
+    })
 }
 
-fn parse2(input: &str) -> Visitor2 {
-    let rows = input.lines().count();
-    let cols = input.lines().next().unwrap().chars().count();
-
-    Visitor2 {
-        costs: input.lines().enumerate().map(|(row, line)| {
-            line.chars().enumerate().map(move |(col, c)| {
-                Cost {
-                    row,
-                    col,
-                    cost: c.to_digit(10).unwrap() as usize,
-                }
-            })
-        })
-        .flatten()
-        .collect(),
+fn parse2(input: &str) -> Result<Visitor2> {
+    let (costs, rows, cols) = parse_costs(input)?;
+
+    Ok(Visitor2 {
+        costs,
         rows,
         cols,
-    }
+    })
 }
 
-fn solve(v: &Visitor) -> usize {
+fn solve(v: &Visitor) -> Result<usize> {
     let start = Position {
         row: 0,
         col: 0,
@@ -403,15 +357,17 @@ fn solve(v: &Visitor) -> usize {
         steps_done: 0,
     };
 
-    if let Some(res) = dijkstra(&start, |p| v.successors(&p), |p| p.row == stop.row && p.col == stop.col) {
-        // v.show_path(&res.0);
-        return res.1;
+    let _span = tracing::debug_span!("dijkstra", rows = v.rows, cols = v.cols).entered();
+    if let Some(res) = dijkstra(&start, |p| v.successors(p), |p| p.row == stop.row && p.col == stop.col) {
+        tracing::debug!(cost = res.1, length = res.0.len(), "path found");
+        tracing::trace!(path = ?res.0);
+        Ok(res.1)
     } else {
-        panic!("no path found");
+        Err(Error::no_solution("no path found"))
     }
 }
 
-fn solve2(v: &Visitor2) -> usize {
+fn solve2(v: &Visitor2) -> Result<usize> {
     let start = Position2 {
         row: 0,
         col: 0,
@@ -428,52 +384,63 @@ fn solve2(v: &Visitor2) -> usize {
         steps_to_do: 0,
     };
 
-    if let Some(res) = dijkstra(&start, |p| v.successors(&p), |p| p.row == stop.row && p.col == stop.col && p.steps_to_do == 0) {
-        // v.show_path(&res.0);
-        return res.1;
+    let _span = tracing::debug_span!("dijkstra", rows = v.rows, cols = v.cols).entered();
+    if let Some(res) = dijkstra(&start, |p| v.successors(p), |p| p.row == stop.row && p.col == stop.col && p.steps_to_do == 0) {
+        tracing::debug!(cost = res.1, length = res.0.len(), "path found");
+        tracing::trace!(path = ?res.0);
+        Ok(res.1)
     } else {
-        panic!("no path found");
+        Err(Error::no_solution("no path found"))
     }
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day17/input2.txt").unwrap();
-    let data = parse(&input);
-    println!("Day 17, Part 1: {}", solve(&data));
-}
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = 17;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day17/input2.txt").unwrap();
-    let data = parse2(&input);
-    println!("Day 17, Part 2: {}", solve2(&data));
+    type Model<'a> = (Visitor, Visitor2);
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        Ok((parse(input)?, parse2(input)?))
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(&model.0)?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve2(&model.1)?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day17/input1.txt").unwrap();
-        let data = super::parse(&input);
-        assert_eq!(102, super::solve(&data));
+        let data = super::parse(&input).unwrap();
+        assert_eq!(102, super::solve(&data).unwrap());
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day17/input2.txt").unwrap();
-        let data = super::parse(&input);
-        assert_eq!(1044, super::solve(&data));
+        let data = super::parse(&input).unwrap();
+        assert_eq!(1044, super::solve(&data).unwrap());
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day17/input1.txt").unwrap();
-        let data = super::parse2(&input);
-        assert_eq!(94, super::solve2(&data));
+        let data = super::parse2(&input).unwrap();
+        assert_eq!(94, super::solve2(&data).unwrap());
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day17/input2.txt").unwrap();
-        let data = super::parse2(&input);
-        assert_eq!(1227, super::solve2(&data));
+        let data = super::parse2(&input).unwrap();
+        assert_eq!(1227, super::solve2(&data).unwrap());
     }
 }
\ No newline at end of file
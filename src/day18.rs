@@ -1,23 +1,13 @@
-#[derive(Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+use crate::{Answer, Solution};
+use crate::error::{self, Result};
+use crate::geometry::{Direction, Polygon};
 
 #[derive(Debug)]
-struct Instruction {
+pub struct Instruction {
     direction:  Direction,
     distance:   usize,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Position {
-    x: i64,
-    y: i64,
-}
-
 // fn from_hex(input: &str) -> Result<u8, std::num::ParseIntError> {
 //   u8::from_str_radix(input, 16)
 // }
@@ -110,78 +100,32 @@ fn parse2(input: &str) -> nom::IResult<&str, Vec<Instruction>> {
     Ok((input, v))
 }
 
-fn shoelace(data: &Vec<Position>) -> i64 {
-    data
-    .windows(2)
-    .fold(0, |acc, v| {
-        let x1 = v[0].x;
-        let y1 = v[0].y;
-        let x2 = v[1].x;
-        let y2 = v[1].y;
-        acc + (x1*y2 - x2*y1)
-    })
-    .abs() / 2
+fn solve(data: &[Instruction]) -> i64 {
+    let polygon = Polygon::from_instructions(data.iter().map(|i| (i.direction, i.distance as i64)));
+    polygon.interior_points() + polygon.boundary_points()
 }
 
-fn pick_theorem(area: i64, n_points: i64) -> i64 {
-    area - (n_points/2) + 1
-}
+pub struct Day18;
 
-fn positions(data: &Vec<Instruction>) -> Vec<Position> {
-    let mut v = Vec::new();
-    let mut pos = Position { x: 0, y: 0 };
-    v.push(pos.clone());
-    for i in data {
-        match i.direction {
-            Direction::Up => {
-                for _ in 0..i.distance {
-                    pos.y += 1;
-                    v.push(pos);
-                }
-            },
-            Direction::Down => {
-                for _ in 0..i.distance {
-                    pos.y -= 1;
-                    v.push(pos);
-                }
-            },
-            Direction::Left => {
-                for _ in 0..i.distance {
-                    pos.x -= 1;
-                    v.push(pos);
-                }
-            },
-            Direction::Right => {
-                for _ in 0..i.distance {
-                    pos.x += 1;
-                    v.push(pos);
-                }
-            },
-        }
-    }
-    v
-}
+impl Solution for Day18 {
+    const DAY: u32 = 18;
 
-fn solve(data: &Vec<Instruction>) -> i64 {
-    let positions = positions(data);
-    let area = shoelace(&positions);
-    let n_points = (positions.len()-1) as i64;
-    let interior = pick_theorem(area, n_points); // should be 24
-    interior + n_points
-}
+    type Model<'a> = (Vec<Instruction>, Vec<Instruction>);
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day18/input2.txt").unwrap();
-    let data = parse(&input).unwrap().1;
-    println!("Day 18, Part 1: {}", solve(&data));
-}
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        Ok((error::complete(input, parse(input))?, error::complete(input, parse2(input))?))
+    }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day18/input2.txt").unwrap();
-    let data = parse2(&input).unwrap().1;
-    println!("Day 18, Part 2: {}", solve(&data));
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(&model.0).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(&model.1).into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
@@ -1,4 +1,6 @@
 use nom::IResult;
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
 
 #[derive(Debug, Eq, PartialEq)]
 enum Operator {
@@ -32,7 +34,7 @@ struct Category {
 
 
 #[derive(Debug)]
-struct Data {
+pub struct Data {
     workflows: Vec<Workflow>,
     categories: Vec<Vec<Category>>,
 }
@@ -101,23 +103,29 @@ fn parse_workflows(input: &str) -> IResult<&str, Vec<Workflow>> {
     Ok((input, v))
 }
 
-fn parse_category(input: &str) -> IResult<&str, Category> {
-    let (input, _category) = nom::character::complete::one_of("xmas")(input)?;
-    let (input, _) = nom::bytes::complete::tag("=")(input)?;
-    let (input, value) = nom::character::complete::u32(input)?;
-    Ok((input, Category{
-        value,
-    }))
+fn parse_category<'a>(name: char) -> impl FnMut(&'a str) -> IResult<&'a str, Category> {
+    move |input| {
+        let (input, _) = nom::character::complete::char(name)(input)?;
+        let (input, _) = nom::bytes::complete::tag("=")(input)?;
+        let (input, value) = nom::character::complete::u32(input)?;
+        Ok((input, Category{
+            value,
+        }))
+    }
 }
 
+/// The ratings of one part, which must be `x`, `m`, `a` and `s` in that order.
 fn parse_category_set(input: &str) -> IResult<&str, Vec<Category>> {
     let (input, _) = nom::bytes::complete::tag("{")(input)?;
-    let (input, v) = nom::multi::separated_list1(
-        nom::bytes::complete::tag(","),
-        parse_category
-    )(input)?;
+    let (input, x) = parse_category('x')(input)?;
+    let (input, _) = nom::bytes::complete::tag(",")(input)?;
+    let (input, m) = parse_category('m')(input)?;
+    let (input, _) = nom::bytes::complete::tag(",")(input)?;
+    let (input, a) = parse_category('a')(input)?;
+    let (input, _) = nom::bytes::complete::tag(",")(input)?;
+    let (input, s) = parse_category('s')(input)?;
     let (input, _) = nom::bytes::complete::tag("}")(input)?;
-    Ok((input, v))
+    Ok((input, vec![x, m, a, s]))
 }
 
 fn parse_categories(input: &str) -> IResult<&str, Vec<Vec<Category>>> {
@@ -140,89 +148,77 @@ fn parse(input: &str) -> IResult<&str, Data> {
     }))
 }
 
-fn solve(data: &Data) -> u32 {
-    data.categories.iter().map(|c|{
-        let x = c[0].value;
-        let m = c[1].value;
-        let a = c[2].value;
-        let s = c[3].value;
-        let mut w = data.workflows.iter().find(|a| a.name == "in").expect("'in' must exist");
-        let mut accept = false;
-        let mut done = false;
-        while !done {
-            for j in w.jumps.iter() {
-                if let Some(c) = &j.condition {
-                    let n = match c.category {
-                        'x' => x,
-                        'm' => m,
-                        'a' => a,
-                        's' => s,
-                        _ => unreachable!()
-                    };
-                    let success;
-                    if c.operator == Operator::GreaterThan && n > c.value {
-                        success = true;
-                    } else if c.operator == Operator::LessThan && n < c.value {
-                        success = true;
-                    } else {
-                        success = false;
-                    }
-                    if success {
-                        match j.destination.as_str() {
-                            "A" => {
-                                accept = true;
-                                done = true;
-                                break;
-                            },
-                            "R" => {
-                                accept = false;
-                                done = true;
-                                break;
-                            },
-                            _ => {
-                                w = data.workflows.iter().find(|a| a.name == j.destination).expect("conditional destination must exist");
-                                break;
-                            }
-                        }
-                    } else {
-                        // continue with the next jump
-                    }
-                } else {
-                    // unconditional jump
-                    match j.destination.as_str() {
-                        "A" => {
-                            accept = true;
-                            done = true;
-                            break;
-                        },
-                        "R" => {
-                            accept = false;
-                            done = true;
-                            break;
-                        },
-                        _ => {
-                            w = data.workflows.iter().find(|a| a.name == j.destination).expect("unconditional destination must exist");
-                            break;
-                        }
-                    }
+fn find_workflow<'a>(data: &'a Data, name: &str) -> Result<&'a Workflow> {
+    data.workflows.iter().find(|a| a.name == name).ok_or_else(|| {
+        Error::no_solution(format!("workflow '{}' does not exist", name))
+    })
+}
+
+/// Runs a part through the workflows from `in`. Coming back to a workflow
+/// would repeat the same jumps forever, so that is an error.
+fn accepts(data: &Data, [x, m, a, s]: [u32; 4]) -> Result<bool> {
+    let mut w = find_workflow(data, "in")?;
+    let mut visited = vec![w.name.as_str()];
+    loop {
+        let jump = w.jumps.iter().find(|j| match &j.condition {
+            Some(c) => {
+                let n = match c.category {
+                    'x' => x,
+                    'm' => m,
+                    'a' => a,
+                    's' => s,
+                    _ => unreachable!()
+                };
+                match c.operator {
+                    Operator::GreaterThan => n > c.value,
+                    Operator::LessThan => n < c.value,
                 }
-            };
-        }
-        if accept {
-            x + m + a + s
-        } else {
-            0
+            },
+            // unconditional jump
+            None => true,
+        });
+        let jump = jump.ok_or_else(|| Error::no_solution(format!("no rule of workflow '{}' applies", w.name)))?;
+        match jump.destination.as_str() {
+            "A" => return Ok(true),
+            "R" => return Ok(false),
+            destination => {
+                if visited.contains(&destination) {
+                    return Err(Error::no_solution(format!("workflow '{}' loops back to '{}'", w.name, destination)));
+                }
+                w = find_workflow(data, destination)?;
+                visited.push(destination);
+            }
         }
-    })
-    .sum()
+    }
+}
+
+fn solve(data: &Data) -> Result<u64> {
+    data.categories
+        .iter()
+        .map(|c| {
+            let part = [c[0].value, c[1].value, c[2].value, c[3].value];
+            Ok(if accepts(data, part)? { part.iter().map(|&n| u64::from(n)).sum() } else { 0 })
+        })
+        .sum()
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day19/input2.txt").unwrap();
-    let (_, data) = parse(&input).unwrap();
-    println!("Day 19, Part 1: {}", solve(&data));
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u32 = 19;
+
+    type Model<'a> = Data;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        error::complete(input, parse(input))
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(solve(model)?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -230,13 +226,31 @@ mod tests {
     fn t1() {
         let input = std::fs::read_to_string("data/day19/input1.txt").unwrap();
         let (_, data) = parse(&input).unwrap();
-        assert_eq!(19114, solve(&data));
+        assert_eq!(19114, solve(&data).unwrap());
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day19/input2.txt").unwrap();
         let (_, data) = parse(&input).unwrap();
-        assert_eq!(263678, solve(&data));
+        assert_eq!(263678, solve(&data).unwrap());
+    }
+
+    #[test]
+    fn t3() {
+        let (_, data) = parse("in{x<5:a,R}\na{x<5:in,A}\n\n{x=1,m=2,a=3,s=4}\n{x=7,m=2,a=3,s=4}").unwrap();
+        assert_eq!(solve(&data).unwrap_err().to_string(), "no solution: workflow 'a' loops back to 'in'");
+        let (_, data) = parse("in{a}\na{x<5:b,a}\nb{R}\n\n{x=7,m=2,a=3,s=4}").unwrap();
+        assert_eq!(solve(&data).unwrap_err().to_string(), "no solution: workflow 'a' loops back to 'a'");
+        let (_, data) = parse("in{x<5:A}\n\n{x=7,m=2,a=3,s=4}").unwrap();
+        assert_eq!(solve(&data).unwrap_err().to_string(), "no solution: no rule of workflow 'in' applies");
+
+        let e = Day19::parse("in{A}\n\n{x=1,a=2,m=3,s=4}").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 3, column 6: expected Char");
+        assert!(Day19::parse("in{A}\n\n{x=1,m=2,a=3}").is_err());
+        assert!(Day19::parse("in{A}\n\n{x=1,m=2,a=3,s=4,x=5}").is_err());
+
+        let (_, data) = parse("in{A}\n\n{x=4000000000,m=4000000000,a=1,s=1}").unwrap();
+        assert_eq!(solve(&data).unwrap(), 8000000002);
     }
 }
@@ -1,4 +1,6 @@
-use nom::{IResult, character, multi, bytes};
+use nom::{IResult, character, multi, bytes, branch};
+use crate::{Answer, Solution};
+use crate::error::{self, Result};
 
 #[derive(Debug)]
 struct Match {
@@ -14,7 +16,7 @@ struct Game {
 }
 
 #[derive(Debug)]
-struct Games {
+pub struct Games {
     games: Vec<Game>
 }
 
@@ -22,7 +24,11 @@ fn parse_color(input: &str) -> IResult<&str, (u32, &str)> {
     // 3 blue
     let (input, count) = character::complete::u32(input)?;
     let (input, _) = bytes::complete::tag(" ")(input)?;
-    let (input, color) = character::complete::alpha1(input)?;
+    let (input, color) = branch::alt((
+        bytes::complete::tag("red"),
+        bytes::complete::tag("green"),
+        bytes::complete::tag("blue")
+    ))(input)?;
 
     Ok((input, (count, color)))
 }
@@ -45,9 +51,7 @@ fn parse_colors(input: &str) -> IResult<&str, Match> {
             "red" => m.red = count,
             "green" => m.green = count,
             "blue" => m.blue = count,
-            _ => {
-                panic!("Unknown color: {}", color);
-            }
+            _ => unreachable!(),
         }
     }
 
@@ -65,7 +69,7 @@ fn parse_game(input: &str) -> IResult<&str, Game> {
     )(input)?;
 
     let game = Game {
-        gid: gid,
+        gid,
         matches: m
     };
 
@@ -73,7 +77,7 @@ fn parse_game(input: &str) -> IResult<&str, Game> {
 }
 
 fn parse_games(input: &str) -> IResult<&str, Games> {
-    let (_, games) = multi::separated_list0(
+    let (input, games) = multi::separated_list0(
         bytes::complete::tag("\n"),
         parse_game
     )(input)?;
@@ -81,12 +85,11 @@ fn parse_games(input: &str) -> IResult<&str, Games> {
     Ok((input, Games { games }))
 }
 
-fn sum_possibile_games(input: &str, available_cubes: Match) -> u32 {
-    let games = parse_games(input).unwrap().1;
+fn sum_possibile_games(games: &Games, available_cubes: Match) -> u32 {
     let mut res = 0;
-    for game in games.games {
+    for game in &games.games {
         let mut possible = true;
-        for m in game.matches {
+        for m in &game.matches {
             if m.red > available_cubes.red || m.green > available_cubes.green || m.blue > available_cubes.blue {
                 possible = false;
             }
@@ -99,14 +102,13 @@ fn sum_possibile_games(input: &str, available_cubes: Match) -> u32 {
     res
 }
 
-fn fewers_number_of_cubes_to_make_it_possible(input: &str) -> u32 {
-    let games = parse_games(input).unwrap().1;
+fn fewers_number_of_cubes_to_make_it_possible(games: &Games) -> u32 {
     let mut res = 0;
-    for game in games.games {
+    for game in &games.games {
         let mut min_red = 0;
         let mut min_green = 0;
         let mut min_blue = 0;
-        for m in game.matches {
+        for m in &game.matches {
             if m.red > min_red {
                 min_red = m.red;
             }
@@ -124,14 +126,24 @@ fn fewers_number_of_cubes_to_make_it_possible(input: &str) -> u32 {
     res
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day2/input2.txt").unwrap();
-    println!("Day 2, Part 1: {}", sum_possibile_games(&input, Match { red: 12, green: 13, blue: 14 }));
-}
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u32 = 2;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day2/input4.txt").unwrap();
-    println!("Day 2, Part 2: {}", fewers_number_of_cubes_to_make_it_possible(&input));
+    type Model<'a> = Games;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        error::complete(input, parse_games(input))
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(sum_possibile_games(model, Match { red: 12, green: 13, blue: 14 }).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(fewers_number_of_cubes_to_make_it_possible(model).into())
+    }
 }
 
 #[cfg(test)]
@@ -139,28 +151,32 @@ mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day2/input1.txt").unwrap();
-        let sum = super::sum_possibile_games(&input, super::Match { red: 12, green: 13, blue: 14 });
+        let games = super::parse_games(&input).unwrap().1;
+        let sum = super::sum_possibile_games(&games, super::Match { red: 12, green: 13, blue: 14 });
         assert_eq!(sum, 8);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day2/input2.txt").unwrap();
-        let sum = super::sum_possibile_games(&input, super::Match { red: 12, green: 13, blue: 14 });
+        let games = super::parse_games(&input).unwrap().1;
+        let sum = super::sum_possibile_games(&games, super::Match { red: 12, green: 13, blue: 14 });
         assert_eq!(sum, 2149);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day2/input3.txt").unwrap();
-        let sum = super::fewers_number_of_cubes_to_make_it_possible(&input);
+        let games = super::parse_games(&input).unwrap().1;
+        let sum = super::fewers_number_of_cubes_to_make_it_possible(&games);
         assert_eq!(sum, 2286);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day2/input4.txt").unwrap();
-        let sum = super::fewers_number_of_cubes_to_make_it_possible(&input);
+        let games = super::parse_games(&input).unwrap().1;
+        let sum = super::fewers_number_of_cubes_to_make_it_possible(&games);
         assert_eq!(sum, 71274);
     }
 }
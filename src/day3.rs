@@ -1,9 +1,7 @@
-#[derive(Debug)]
-struct Matrix {
-    rows: usize,
-    columns: usize,
-    data: Vec<char>,
-}
+use std::str::FromStr;
+use crate::{Answer, Solution};
+use crate::error::{Error, Result};
+use crate::grid::Grid;
 
 #[derive(Debug)]
 struct NumberWithBoundary {
@@ -11,351 +9,317 @@ struct NumberWithBoundary {
     boundary: Vec<char>
 }
 
-impl Matrix {
-    fn new(rows: usize, columns: usize) -> Self {
-        Self {
-            rows,
-            columns,
-            data: Vec::with_capacity(rows * columns),
-        }
-    }
-
-    fn from_str(input: &str) -> Self {
-        let columns = input.find('\n').unwrap();
-        let rows = (input.len() / columns) - 1;
-        assert!(rows > 0);
-        assert!(columns > 0);
-        let mut matrix = Self::new(rows, columns);
-        for c in input.chars() {
-            if c == '\n' {
-                continue;
+/// Rejects numbers too large for the solvers, so `number` can't fail later.
+fn parse(input: &str) -> Result<Grid<char>> {
+    let grid = Grid::parse(input)?;
+    for (r, row) in grid.iter_rows().enumerate() {
+        let mut c = 0;
+        for run in row.chunk_by(|a, b| a.is_ascii_digit() == b.is_ascii_digit()) {
+            if run[0].is_ascii_digit() && run.iter().collect::<String>().parse::<u32>().is_err() {
+                return Err(Error::parse(r + 1, c + 1, "number does not fit in 32 bits"));
             }
-            matrix.data.push(c);
+            c += run.len();
         }
-        matrix
     }
+    Ok(grid)
+}
 
-    fn at(&self, row: usize, column: usize) -> char {
-        self.data[row * self.columns + column]
-    }
-
-    fn boundary(&self, row: usize, column: usize, left: bool, right: bool) -> Vec<char> {
-        let mut boundary = Vec::new();
-        // Top
-        if row > 0 && column > 0 {
-            boundary.push(self.at(row - 1, column - 1));
-        }
-        if row > 0 {
-            boundary.push(self.at(row - 1, column));
-        }
-        if row > 0 && column < self.columns - 1 {
-            boundary.push(self.at(row - 1, column + 1));
-        }
-
-        // Current
-        if column > 0 && left {
-            boundary.push(self.at(row, column - 1));
-        }
-        if column < self.columns - 1 && right {
-            boundary.push(self.at(row, column + 1));
-        }
+/// The number spelled by `digits`, which `parse` has checked.
+fn number<T: FromStr>(digits: &[char]) -> T {
+    digits
+        .iter()
+        .collect::<String>()
+        .parse()
+        .unwrap_or_else(|_| unreachable!("numbers are checked by parse"))
+}
 
-        // Bottom
-        if row < self.rows - 1 && column > 0 {
-            boundary.push(self.at(row + 1, column - 1));
-        }
-        if row < self.rows - 1 {
-            boundary.push(self.at(row + 1, column));
-        }
-        if row < self.rows - 1 && column < self.columns - 1 {
-            boundary.push(self.at(row + 1, column + 1));
-        }
-        boundary
-    }
+fn boundary(matrix: &Grid<char>, row: usize, column: usize, left: bool, right: bool) -> Vec<char> {
+    matrix
+        .neighbours8(row, column)
+        .filter(|&(r, c)| r != row || (c < column && left) || (c > column && right))
+        .map(|position| matrix[position])
+        .collect()
+}
 
-    fn unique_symbols_in_boundary(&self) -> Vec<NumberWithBoundary> {
-        let mut res = Vec::new();
-        for r in 0..self.rows {
-            let mut in_a_digit = false;
-            let mut current_boundary = Vec::new();
-            let mut current_digit = Vec::new();
-            for c in 0..self.columns {
-                let cell = self.at(r, c);
-                if cell.is_digit(10) {
-                    if !in_a_digit {
-                        in_a_digit = true;
-                        let left = true;
-                        let mut right = true;
-                        if c < self.columns - 1 && self.at(r, c + 1).is_digit(10) {
-                            right = false;
-                        }
-                        current_boundary = self.boundary(r, c, left, right);
-                        current_digit = vec![cell];
-                    } else {
-                        let left = false;
-                        let mut right = true;
-                        if c < self.columns - 1 && self.at(r, c + 1).is_digit(10) {
-                            right = false;
-                        }
-                        current_boundary.extend(self.boundary(r, c, left, right));
-                        current_digit.push(cell);
+fn unique_symbols_in_boundary(matrix: &Grid<char>) -> Vec<NumberWithBoundary> {
+    let mut res = Vec::new();
+    for r in 0..matrix.rows() {
+        let mut in_a_digit = false;
+        let mut current_boundary = Vec::new();
+        let mut current_digit = Vec::new();
+        for c in 0..matrix.cols() {
+            let cell = matrix[(r, c)];
+            if cell.is_ascii_digit() {
+                if !in_a_digit {
+                    in_a_digit = true;
+                    let left = true;
+                    let mut right = true;
+                    if c < matrix.cols() - 1 && matrix[(r, c + 1)].is_ascii_digit() {
+                        right = false;
                     }
+                    current_boundary = boundary(matrix, r, c, left, right);
+                    current_digit = vec![cell];
                 } else {
-                    if in_a_digit {
-                        in_a_digit = false;
-                        current_boundary.retain(|&x| x != '.');
-                        current_boundary.sort();
-                        current_boundary.dedup();
-                        let current_digit = current_digit.iter().collect::<String>();
-                        let current_digit = current_digit.parse::<usize>().unwrap();
-                        res.push(NumberWithBoundary {
-                            number: current_digit,
-                            boundary: current_boundary.clone()
-                        });
+                    let left = false;
+                    let mut right = true;
+                    if c < matrix.cols() - 1 && matrix[(r, c + 1)].is_ascii_digit() {
+                        right = false;
                     }
+                    current_boundary.extend(boundary(matrix, r, c, left, right));
+                    current_digit.push(cell);
                 }
-                if c == self.columns - 1 && in_a_digit {
+            } else {
+                if in_a_digit {
                     in_a_digit = false;
                     current_boundary.retain(|&x| x != '.');
                     current_boundary.sort();
                     current_boundary.dedup();
-                    let current_digit = current_digit.iter().collect::<String>();
-                    let current_digit = current_digit.parse::<usize>().unwrap();
+                    let current_digit = number::<usize>(&current_digit);
                     res.push(NumberWithBoundary {
                         number: current_digit,
                         boundary: current_boundary.clone()
                     });
                 }
             }
+            if c == matrix.cols() - 1 && in_a_digit {
+                in_a_digit = false;
+                current_boundary.retain(|&x| x != '.');
+                current_boundary.sort();
+                current_boundary.dedup();
+                let current_digit = number::<usize>(&current_digit);
+                res.push(NumberWithBoundary {
+                    number: current_digit,
+                    boundary: current_boundary.clone()
+                });
+            }
         }
-        res
     }
+    res
 }
 
-fn sum_number_with_boundaries(input: &str) -> usize {
-    let matrix = Matrix::from_str(input);
-
-    let boundary = matrix.unique_symbols_in_boundary();
+fn sum_number_with_boundaries(matrix: &Grid<char>) -> usize {
+    let boundary = unique_symbols_in_boundary(matrix);
     let mut sum_touching = 0;
     for n in boundary {
-        if n.boundary.len() > 0 {
+        if !n.boundary.is_empty() {
             sum_touching += n.number;
         }
     }
     sum_touching
 }
 
-fn north_west(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn north_west(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if r>0 && c>0 && m.at(r-1, c-1).is_digit(10) {
-            v.push(m.at(r-1, c-1));
+        if r>0 && c>0 && m.get(r-1, c-1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r-1, c-1)]);
         } else {
             break;
         }
-        c = c - 1;
+        c -= 1;
     }
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
     v.reverse();
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn north_east(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn north_east(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if r>0 && c<m.columns && m.at(r-1, c+1).is_digit(10) {
-            v.push(m.at(r-1, c+1));
+        if r>0 && c<m.cols() && m.get(r-1, c+1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r-1, c+1)]);
         } else {
             break;
         }
-        c = c + 1;
+        c += 1;
     }
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn north(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
-    if r>0 && m.at(r-1, c).is_digit(10) {
+fn north(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
+    if r>0 && m.get(r-1, c).is_some_and(char::is_ascii_digit) {
         // there is a number above
         let mut v = Vec::new();
         loop {
-            if c>0 && m.at(r-1, c).is_digit(10) {
-                c = c - 1;
+            if c>0 && m.get(r-1, c).is_some_and(char::is_ascii_digit) {
+                c -= 1;
             } else {
-                c = c + 1;
+                c += 1;
                 break;
             }
         }
         loop {
-            if m.at(r-1, c).is_digit(10) {
-                v.push(m.at(r-1, c));
+            if m.get(r-1, c).is_some_and(char::is_ascii_digit) {
+                v.push(m[(r-1, c)]);
             } else {
                 break;
             }
-            c = c + 1;
+            c += 1;
         }
-        return Some(v.iter().collect::<String>().parse::<u32>().unwrap());
+        return Some(number(&v));
     }
 
     None
 }
 
-fn south(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
-    if r<m.rows && m.at(r+1, c).is_digit(10) {
+fn south(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
+    if r<m.rows() && m.get(r+1, c).is_some_and(char::is_ascii_digit) {
         // there is a number below
         let mut v = Vec::new();
         loop {
-            if c>0 && m.at(r+1, c).is_digit(10) {
-                c = c - 1;
+            if c>0 && m.get(r+1, c).is_some_and(char::is_ascii_digit) {
+                c -= 1;
             } else {
-                c = c + 1;
+                c += 1;
                 break;
             }
         }
         loop {
-            if m.at(r+1, c).is_digit(10) {
-                v.push(m.at(r+1, c));
+            if m.get(r+1, c).is_some_and(char::is_ascii_digit) {
+                v.push(m[(r+1, c)]);
             } else {
                 break;
             }
-            c = c + 1;
+            c += 1;
         }
-        return Some(v.iter().collect::<String>().parse::<u32>().unwrap());
+        return Some(number(&v));
     }
 
     None
 }
 
-fn west(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn west(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if c>0 && m.at(r, c-1).is_digit(10) {
-            v.push(m.at(r, c-1));
+        if c>0 && m.get(r, c-1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r, c-1)]);
         } else {
             break;
         }
-        c = c - 1;
+        c -= 1;
     }
     v.reverse();
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn east(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn east(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if c<m.columns && m.at(r, c+1).is_digit(10) {
-            v.push(m.at(r, c+1));
+        if c<m.cols() && m.get(r, c+1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r, c+1)]);
         } else {
             break;
         }
-        c = c + 1;
+        c += 1;
     }
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn south_west(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn south_west(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if r<m.rows && c>0 && m.at(r+1, c-1).is_digit(10) {
-            v.push(m.at(r+1, c-1));
+        if r<m.rows() && c>0 && m.get(r+1, c-1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r+1, c-1)]);
         } else {
             break;
         }
-        c = c - 1;
+        c -= 1;
     }
     v.reverse();
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn south_east(m: &Matrix, r: usize, mut c: usize) -> Option<u32> {
+fn south_east(m: &Grid<char>, r: usize, mut c: usize) -> Option<u32> {
     let mut v = Vec::new();
     loop {
-        if r<m.rows && c<m.columns && m.at(r+1, c+1).is_digit(10) {
-            v.push(m.at(r+1, c+1));
+        if r<m.rows() && c<m.cols() && m.get(r+1, c+1).is_some_and(char::is_ascii_digit) {
+            v.push(m[(r+1, c+1)]);
         } else {
             break;
         }
-        c = c + 1;
+        c += 1;
     }
-    if v.len() == 0 {
+    if v.is_empty() {
         return None;
     }
-    Some(v.iter().collect::<String>().parse::<u32>().unwrap())
+    Some(number(&v))
 }
 
-fn sum_gears_power(input: &str) -> u32 {
-    let matrix = Matrix::from_str(input);
-    let mut sum = 0;
-    for r in 0..matrix.rows {
-        for c in 0..matrix.columns {
-            if matrix.at(r, c) == '*' {
+fn sum_gears_power(matrix: &Grid<char>) -> Result<u64> {
+    let mut sum: u64 = 0;
+    for r in 0..matrix.rows() {
+        for c in 0..matrix.cols() {
+            if matrix[(r, c)] == '*' {
                 let mut nums = Vec::new();
-                let n = north(&matrix, r, c);
-                if n.is_some() {
-                    nums.push(n.unwrap());
+                if let Some(n) = north(matrix, r, c) {
+                    nums.push(n);
                 } else {
-                    let nw = north_west(&matrix, r, c);
-                    if nw.is_some() {
-                        nums.push(nw.unwrap());
+                    if let Some(nw) = north_west(matrix, r, c) {
+                        nums.push(nw);
                     }
-                    let ne = north_east(&matrix, r, c);
-                    if ne.is_some() {
-                        nums.push(ne.unwrap());
+                    if let Some(ne) = north_east(matrix, r, c) {
+                        nums.push(ne);
                     }
                 }
-                let s = south(&matrix, r, c);
-                if s.is_some() {
-                    nums.push(s.unwrap());
+                if let Some(s) = south(matrix, r, c) {
+                    nums.push(s);
                 } else {
-                    let sw = south_west(&matrix, r, c);
-                    if sw.is_some() {
-                        nums.push(sw.unwrap());
+                    if let Some(sw) = south_west(matrix, r, c) {
+                        nums.push(sw);
                     }
-                    let se = south_east(&matrix, r, c);
-                    if se.is_some() {
-                        nums.push(se.unwrap());
+                    if let Some(se) = south_east(matrix, r, c) {
+                        nums.push(se);
                     }
                 }
-                let w = west(&matrix, r, c);
-                if w.is_some() {
-                    nums.push(w.unwrap());
+                if let Some(w) = west(matrix, r, c) {
+                    nums.push(w);
                 }
-                let e = east(&matrix, r, c);
-                if e.is_some() {
-                    nums.push(e.unwrap());
+                if let Some(e) = east(matrix, r, c) {
+                    nums.push(e);
                 }
                 if nums.len() == 2 {
-                    let power = nums[0] * nums[1];
-                    sum += power;
+                    let power = u64::from(nums[0]) * u64::from(nums[1]);
+                    sum = sum.checked_add(power).ok_or_else(|| Error::no_solution("the sum of gear powers does not fit in 64 bits"))?;
                 }
             }
         }
     }
 
-    sum
+    Ok(sum)
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day3/input2.txt").unwrap();
-    println!("Day 3, Part 1: {}", sum_number_with_boundaries(&input));
-}
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+
+    type Model<'a> = Grid<char>;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day3/input4.txt").unwrap();
-    println!("Day 3, Part 2: {}", sum_gears_power(&input));
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(sum_number_with_boundaries(model).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(sum_gears_power(model)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -363,28 +327,38 @@ mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day3/input1.txt").unwrap();
-        let n = super::sum_number_with_boundaries(&input);
+        let n = super::sum_number_with_boundaries(&super::Grid::parse(&input).unwrap());
         assert_eq!(n, 4361);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day3/input2.txt").unwrap();
-        let n = super::sum_number_with_boundaries(&input);
+        let n = super::sum_number_with_boundaries(&super::Grid::parse(&input).unwrap());
         assert_eq!(n, 514969);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day3/input3.txt").unwrap();
-        let n = super::sum_gears_power(&input);
+        let n = super::sum_gears_power(&super::Grid::parse(&input).unwrap()).unwrap();
         assert_eq!(n, 467835);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day3/input4.txt").unwrap();
-        let n = super::sum_gears_power(&input);
+        let n = super::sum_gears_power(&super::Grid::parse(&input).unwrap()).unwrap();
         assert_eq!(n, 78915902);
     }
+
+    #[test]
+    fn t5() {
+        let e = super::parse("*99999999999\n............\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 1, column 2: number does not fit in 32 bits");
+        let grid = super::parse("4294967295*4294967295\n").unwrap();
+        assert_eq!(super::sum_gears_power(&grid).unwrap(), 4294967295 * 4294967295);
+        let grid = super::parse("4294967295*4294967295*4294967295\n").unwrap();
+        assert!(super::sum_gears_power(&grid).is_err());
+    }
 }
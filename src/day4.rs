@@ -1,8 +1,10 @@
 use nom::{IResult, character, multi, bytes};
 use std::collections::VecDeque;
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
 
 #[derive(Debug, Clone)]
-struct Card {
+pub struct Card {
     cid: u32,
     value: u32,
     winning_numbers: Vec<u32>,
@@ -30,7 +32,7 @@ fn parse_card(input: &str) -> IResult<&str, Card> {
 
     let mut value = 0;
     for available_number in &available_numbers {
-        if winning_numbers.contains(&available_number) {
+        if winning_numbers.contains(available_number) {
             value += 1;
         }
     }
@@ -53,13 +55,12 @@ fn parse_cards(input: &str) -> IResult<&str, Vec<Card>> {
     Ok((input, v))
 }
 
-fn compute_score(input: &str) -> u32 {
-    let (_, cards) = parse_cards(input).unwrap();
+fn compute_score(cards: &[Card]) -> u32 {
     let mut res = 0;
     for card in cards {
         let mut n = 0;
-        for available_number in card.available_numbers {
-            if card.winning_numbers.contains(&available_number) {
+        for available_number in &card.available_numbers {
+            if card.winning_numbers.contains(available_number) {
                 if n == 0 {
                     n = 1;
                 } else {
@@ -72,32 +73,40 @@ fn compute_score(input: &str) -> u32 {
     res
 }
 
-fn duplicate_cards_then_count(input: &str) -> u32 {
-    let (_, cards) = parse_cards(input).unwrap();
-    let mut deck = VecDeque::from(cards.clone());
+fn duplicate_cards_then_count(cards: &[Card]) -> Result<u32> {
+    let mut deck = VecDeque::from(cards.to_vec());
     let mut card_count = deck.len() as u32;
-    loop {
-        let card = deck.pop_front().unwrap();
+    while let Some(card) = deck.pop_front() {
         for i in 0..card.value {
             let id_to_copy = card.cid + i;
             card_count += 1;
-            deck.push_back(cards[id_to_copy as usize].clone());
-        }
-        if deck.is_empty() {
-            break;
+            let copy = cards.get(id_to_copy as usize).ok_or_else(|| {
+                Error::no_solution(format!("card {} wins a copy of missing card {}", card.cid, id_to_copy + 1))
+            })?;
+            deck.push_back(copy.clone());
         }
     }
-    card_count
+    Ok(card_count)
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day4/input2.txt").unwrap();
-    println!("Day 4, Part 1: {}", compute_score(&input));
-}
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u32 = 4;
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day4/input4.txt").unwrap();
-    println!("Day 4, Part 2: {}", duplicate_cards_then_count(&input));
+    type Model<'a> = Vec<Card>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        error::complete(input, parse_cards(input))
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(compute_score(model).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(duplicate_cards_then_count(model)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -105,24 +114,24 @@ mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day4/input1.txt").unwrap();
-        assert_eq!(super::compute_score(&input), 13);
+        assert_eq!(super::compute_score(&super::parse_cards(&input).unwrap().1), 13);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day4/input2.txt").unwrap();
-        assert_eq!(super::compute_score(&input), 21138);
+        assert_eq!(super::compute_score(&super::parse_cards(&input).unwrap().1), 21138);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day4/input3.txt").unwrap();
-        assert_eq!(super::duplicate_cards_then_count(&input), 30);
+        assert_eq!(super::duplicate_cards_then_count(&super::parse_cards(&input).unwrap().1).unwrap(), 30);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day4/input4.txt").unwrap();
-        assert_eq!(super::duplicate_cards_then_count(&input), 7185540);
+        assert_eq!(super::duplicate_cards_then_count(&super::parse_cards(&input).unwrap().1).unwrap(), 7185540);
     }
 }
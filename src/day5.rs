@@ -1,9 +1,43 @@
 use nom::{IResult, character, multi, bytes};
-use indicatif::*;
+use std::ops::Range;
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MapRange {
+    destination: u64,
+    source: u64,
+    len: u64,
+}
+
+#[derive(Debug)]
+struct SeedMap<'a> {
+    source: &'a str,
+    destination: &'a str,
+    /// Sorted by source.
+    ranges: Vec<MapRange>,
+}
 
 #[derive(Debug)]
-struct SeedMap {
-    ranges: Vec<Vec<u64>>,
+pub struct Almanac<'a> {
+    seeds: Vec<u64>,
+    maps: Vec<SeedMap<'a>>,
+}
+
+impl SeedMap<'_> {
+    fn forward(&self, key: u64) -> u64 {
+        self.ranges
+            .iter()
+            .find(|r| key >= r.source && key < r.source + r.len)
+            .map_or(key, |r| r.destination + (key - r.source))
+    }
+
+    fn reverse(&self, key: u64) -> u64 {
+        self.ranges
+            .iter()
+            .find(|r| key >= r.destination && key < r.destination + r.len)
+            .map_or(key, |r| r.source + (key - r.destination))
+    }
 }
 
 fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
@@ -19,26 +53,40 @@ fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
     Ok((input, v))
 }
 
-fn parse_map(input: &str) -> IResult<&str, SeedMap> {
+fn parse_range(input: &str) -> IResult<&str, MapRange> {
+    // 50 98 2
+    let (input, destination) = character::complete::u64(input)?;
+    let (input, _) = character::complete::space1(input)?;
+    let (input, source) = character::complete::u64(input)?;
+    let (input, _) = character::complete::space1(input)?;
+    let (input, len) = character::complete::u64(input)?;
+
+    Ok((input, MapRange { destination, source, len }))
+}
+
+fn parse_map(input: &str) -> IResult<&str, SeedMap<'_>> {
     // seed-to-soil map:
     // 50 98 2
     // 52 50 48
-    let (input, _name) = character::complete::not_line_ending(input)?;
+    let (input, source) = character::complete::alpha1(input)?;
+    let (input, _) = bytes::complete::tag("-to-")(input)?;
+    let (input, destination) = character::complete::alpha1(input)?;
+    let (input, _) = bytes::complete::tag(" map:")(input)?;
     let (input, _) = character::complete::line_ending(input)?;
-    let (input, ranges) = multi::separated_list0(
+    let (input, mut ranges) = multi::separated_list0(
         character::complete::line_ending,
-        multi::separated_list1(
-            character::complete::space1,
-            character::complete::u64
-        )
+        parse_range
     )(input)?;
+    ranges.sort_by_key(|r| r.source);
 
-    Ok((input, SeedMap{
+    Ok((input, SeedMap {
+        source,
+        destination,
         ranges
     }))
 }
 
-fn parse_maps(input: &str) -> IResult<&str, Vec<SeedMap>> {
+fn parse_maps(input: &str) -> IResult<&str, Vec<SeedMap<'_>>> {
     let (input, m) = multi::separated_list1(
         bytes::complete::tag("\n\n"),
         parse_map
@@ -47,23 +95,22 @@ fn parse_maps(input: &str) -> IResult<&str, Vec<SeedMap>> {
     Ok((input, m))
 }
 
-fn find_closest_location(seeds: &Vec<u64>, maps: &Vec<SeedMap>) -> u64 {
+fn parse_almanac(input: &str) -> IResult<&str, Almanac<'_>> {
+    let (input, seeds) = parse_seeds(input)?;
+    let (input, maps) = parse_maps(input)?;
+
+    Ok((input, Almanac {
+        seeds,
+        maps
+    }))
+}
+
+fn find_closest_location(seeds: &[u64], maps: &[SeedMap]) -> u64 {
     let mut closest_location = u64::MAX;
     for seed in seeds {
         let mut key = *seed;
         for map in maps {
-            let mut mapping = key;
-            for range in &map.ranges {
-                let dst = range[0];
-                let src = range[1];
-                let len = range[2];
-                if key >= src && key < src+len {
-                    let distance = key - src;
-                    mapping = dst + distance;
-                    break;
-                }
-            }
-            key = mapping;
+            key = map.forward(key);
         }
         if key < closest_location {
             closest_location = key;
@@ -72,51 +119,148 @@ fn find_closest_location(seeds: &Vec<u64>, maps: &Vec<SeedMap>) -> u64 {
     closest_location
 }
 
-fn find_closest_location2(seeds: &Vec<u64>, maps: &Vec<SeedMap>) -> u64 {
-    let mut location = 0;
-    let pb = ProgressBar::new_spinner();
-    pb.enable_steady_tick(std::time::Duration::from_millis(100));
-    pb.set_message("Processing...");
-    for _ in 0..u64::MAX {
-        let mut key = location;
-        for map in maps.iter().rev() {
-            for range in &map.ranges {
-                let dst = range[1];
-                let src = range[0];
-                let len = range[2];
-                if key >= src && key < src+len {
-                    let distance = key - src;
-                    key = dst + distance;
-                    break;
-                }
+/// Splits `range` into the pieces covered by each of the map's ranges (and the
+/// gaps between them, which map to themselves), as (source, destination) pairs.
+fn split(map: &SeedMap, range: Range<u64>) -> Result<Vec<(Range<u64>, Range<u64>)>> {
+    let overflow = || Error::invalid(format!("a `{}` to `{}` range does not fit in 64 bits", map.source, map.destination));
+    let mut pieces = Vec::new();
+    let mut cursor = range.start;
+    for r in &map.ranges {
+        let (dst, src) = (r.destination, r.source);
+        let end = src.checked_add(r.len).ok_or_else(overflow)?;
+        if end <= cursor {
+            continue;
+        }
+        if src >= range.end {
+            break;
+        }
+        if src > cursor {
+            pieces.push((cursor..src, cursor..src));
+        }
+        let lo = cursor.max(src);
+        let hi = range.end.min(end);
+        let start = dst.checked_add(lo - src).ok_or_else(overflow)?;
+        pieces.push((lo..hi, start..start.checked_add(hi - lo).ok_or_else(overflow)?));
+        cursor = hi;
+    }
+    if cursor < range.end {
+        pieces.push((cursor..range.end, cursor..range.end));
+    }
+    Ok(pieces)
+}
+
+impl<'a> Almanac<'a> {
+    /// Checks that the maps lead from `seed` to `location`, each one picking
+    /// up where the previous one left off and never coming back to a category,
+    /// and that no range runs past the largest number.
+    fn validate(&self, input: &str) -> Result<()> {
+        let at = |name: &str| &input[name.as_ptr() as usize - input.as_ptr() as usize..];
+        let mut seen = vec!["seed"];
+        for map in &self.maps {
+            let expected = seen[seen.len() - 1];
+            if map.source != expected {
+                return Err(Error::parse_at(input, at(map.source), format!("expected a map from `{}`", expected)));
+            }
+            if seen.contains(&map.destination) {
+                return Err(Error::parse_at(input, at(map.destination), format!("`{}` is mapped to twice", map.destination)));
+            }
+            if map.ranges.iter().any(|r| r.source.checked_add(r.len).is_none() || r.destination.checked_add(r.len).is_none()) {
+                return Err(Error::parse_at(input, at(map.source), "a range of this map does not fit in 64 bits"));
             }
+            seen.push(map.destination);
         }
-        let iter = seeds.chunks(2);
-        for v in iter {
-            let start = v[0];
-            let end = v[0] + v[1];
-            if key >= start && key < end {
-                pb.finish_with_message("Location found");
-                return location;
+        if let Some(last) = self.maps.last().filter(|map| map.destination != "location") {
+            return Err(Error::parse_at(input, at(last.destination), "expected the last map to lead to `location`"));
+        }
+        Ok(())
+    }
+
+    /// Every category in chain order, from `seed` onwards.
+    pub fn categories(&self) -> Vec<&'a str> {
+        let first = self.maps.first().map(|m| m.source);
+        first.into_iter().chain(self.maps.iter().map(|m| m.destination)).collect()
+    }
+
+    /// Converts `value` from one category to another, running the maps in
+    /// reverse when `to` comes before `from` (e.g. `humidity` to `seed`).
+    pub fn lookup(&self, from: &str, to: &str, value: u64) -> Result<u64> {
+        let categories = self.categories();
+        let position = |name: &str| {
+            categories
+                .iter()
+                .position(|&c| c == name)
+                .ok_or_else(|| Error::invalid(format!("unknown category `{}`", name)))
+        };
+        let (from, to) = (position(from)?, position(to)?);
+        if from <= to {
+            Ok(self.maps[from..to].iter().fold(value, |key, map| map.forward(key)))
+        } else {
+            Ok(self.maps[to..from].iter().rev().fold(value, |key, map| map.reverse(key)))
+        }
+    }
+
+    /// Pushes every (start, length) seed range through all the maps, returning
+    /// which seeds land on which locations, ordered by location.
+    pub fn map_seed_ranges(&self) -> Result<Vec<(Range<u64>, Range<u64>)>> {
+        map_seed_ranges(&self.seeds, &self.maps)
+    }
+}
+
+fn map_seed_ranges(seeds: &[u64], maps: &[SeedMap]) -> Result<Vec<(Range<u64>, Range<u64>)>> {
+    if !seeds.len().is_multiple_of(2) {
+        return Err(Error::no_solution("seeds must come in (start, length) pairs"));
+    }
+    let mut pieces: Vec<(Range<u64>, Range<u64>)> = seeds
+        .chunks(2)
+        .filter(|v| v[1] > 0)
+        .map(|v| {
+            let end = v[0]
+                .checked_add(v[1])
+                .ok_or_else(|| Error::invalid(format!("seed range {} {} does not fit in 64 bits", v[0], v[1])))?;
+            Ok((v[0]..end, v[0]..end))
+        })
+        .collect::<Result<_>>()?;
+    for map in maps {
+        let mut next = Vec::new();
+        for (seeds, current) in pieces {
+            for (src, dst) in split(map, current.clone())? {
+                let start = seeds.start + (src.start - current.start);
+                next.push((start..start + (src.end - src.start), dst));
             }
         }
-        location += 1;
+        pieces = next;
     }
-    panic!("Location not found");
+    pieces.sort_by_key(|(seeds, location)| (location.start, seeds.start));
+    Ok(pieces)
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day5/input2.txt").unwrap();
-    let (input, seeds) = parse_seeds(&input).unwrap();
-    let (_, maps) = parse_maps(input).unwrap();
-    println!("Day 5, Part 1: {}", find_closest_location(&seeds, &maps));
+fn find_closest_location2(seeds: &[u64], maps: &[SeedMap]) -> Result<u64> {
+    map_seed_ranges(seeds, maps)?
+        .first()
+        .map(|(_, location)| location.start)
+        .ok_or_else(|| Error::no_solution("location not found"))
 }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day5/input2.txt").unwrap();
-    let (input, seeds) = parse_seeds(&input).unwrap();
-    let (_, maps) = parse_maps(input).unwrap();
-    println!("Day 5, Part 2: {}", find_closest_location2(&seeds, &maps));
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u32 = 5;
+
+    type Model<'a> = Almanac<'a>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        let almanac = error::complete(input, parse_almanac(input))?;
+        almanac.validate(input)?;
+        Ok(almanac)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(find_closest_location(&model.seeds, &model.maps).into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(find_closest_location2(&model.seeds, &model.maps)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -142,7 +286,7 @@ mod tests {
         let input = std::fs::read_to_string("data/day5/input1.txt").unwrap();
         let (input, seeds) = super::parse_seeds(&input).unwrap();
         let (_, maps) = super::parse_maps(input).unwrap();
-        assert_eq!(46, super::find_closest_location2(&seeds, &maps));
+        assert_eq!(46, super::find_closest_location2(&seeds, &maps).unwrap());
     }
 
     #[test]
@@ -150,7 +294,48 @@ mod tests {
         let input = std::fs::read_to_string("data/day5/input2.txt").unwrap();
         let (input, seeds) = super::parse_seeds(&input).unwrap();
         let (_, maps) = super::parse_maps(input).unwrap();
-        assert_eq!(20358599, super::find_closest_location2(&seeds, &maps));
+        assert_eq!(20358599, super::find_closest_location2(&seeds, &maps).unwrap());
+    }
+
+    #[test]
+    fn t5() {
+        let input = std::fs::read_to_string("data/day5/input1.txt").unwrap();
+        let (input, seeds) = super::parse_seeds(&input).unwrap();
+        let (_, maps) = super::parse_maps(input).unwrap();
+        let mapped = super::map_seed_ranges(&seeds, &maps).unwrap();
+        assert_eq!((mapped[0].0.start, mapped[0].1.start), (82, 46));
+        assert_eq!(mapped.iter().map(|(s, _)| s.end - s.start).sum::<u64>(), 27);
+        for (s, l) in &mapped {
+            assert_eq!(s.end - s.start, l.end - l.start);
+            assert_eq!(super::find_closest_location(&[s.start], &maps), l.start);
+        }
     }
-}
 
+    #[test]
+    fn t6() {
+        use crate::Solution;
+        let input = std::fs::read_to_string("data/day5/input1.txt").unwrap();
+        let almanac = super::Day5::parse(&input).unwrap();
+        assert_eq!(almanac.categories().len(), 8);
+        assert_eq!(almanac.lookup("seed", "location", 79).unwrap(), 82);
+        assert_eq!(almanac.lookup("seed", "humidity", 79).unwrap(), 78);
+        assert_eq!(almanac.lookup("humidity", "seed", 78).unwrap(), 79);
+        assert_eq!(almanac.lookup("light", "light", 5).unwrap(), 5);
+        assert!(almanac.lookup("seed", "dirt", 79).is_err());
+
+        let input = input.replace("fertilizer-to-water", "soil-to-water");
+        let e = super::Day5::parse(&input).unwrap_err();
+        assert!(e.to_string().ends_with("expected a map from `fertilizer`"), "{}", e);
+        assert_eq!(almanac.lookup("seed", "dirt", 79).unwrap_err().to_string(), "invalid: unknown category `dirt`");
+
+        let input = std::fs::read_to_string("data/day5/input1.txt").unwrap();
+        let e = super::Day5::parse(&input.replace("humidity-to-location", "humidity-to-place")).unwrap_err();
+        assert!(e.to_string().ends_with("expected the last map to lead to `location`"), "{}", e);
+        let e = super::Day5::parse(&input.replace("-to-water", "-to-soil").replace("water-to-", "soil-to-")).unwrap_err();
+        assert!(e.to_string().ends_with("`soil` is mapped to twice"), "{}", e);
+        let e = super::Day5::parse(&input.replace("50 98 2", "50 18446744073709551615 2")).unwrap_err();
+        assert!(e.to_string().ends_with("does not fit in 64 bits"), "{}", e);
+        let e = super::map_seed_ranges(&[u64::MAX, 2], &almanac.maps).unwrap_err();
+        assert_eq!(e.to_string(), "invalid: seed range 18446744073709551615 2 does not fit in 64 bits");
+    }
+}
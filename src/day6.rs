@@ -1,6 +1,15 @@
 use nom::*;
-use rayon::prelude::*;
-use indicatif::*;
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
+
+pub struct Races {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+    /// Part 2 reads the digits of each line as one number, which may be too
+    /// large even when every part 1 race fits, so it is only parsed there.
+    time: String,
+    distance: String,
+}
 
 fn parse_line1(input: &str) -> IResult<&str, Vec<u64>> {
     let (input, _) = bytes::complete::take_until(":")(input)?;
@@ -15,62 +24,92 @@ fn parse_line1(input: &str) -> IResult<&str, Vec<u64>> {
     Ok((input, v))
 }
 
-fn parse_line2(input: &str) -> IResult<&str, u64> {
+fn parse_line2(input: &str) -> IResult<&str, String> {
     let (input, _) = bytes::complete::take_until(":")(input)?;
     let (input, _) = character::complete::char(':')(input)?;
     let (input, _) = multi::many1(character::complete::space1)(input)?;
     let (input, v) = multi::separated_list0(
         multi::many1(character::complete::char(' ')),
-        multi::many1(character::complete::alphanumeric1).map(|x| x.concat())
+        character::complete::digit1
     )(input)?;
     let (input, _) = character::complete::line_ending(input)?;
 
-    let n = v.concat().parse::<u64>().unwrap();
+    Ok((input, v.concat()))
+}
 
-    Ok((input, n))
+fn concatenated(digits: &str) -> Result<u64> {
+    digits
+        .parse::<u64>()
+        .map_err(|_| Error::invalid(format!("{} does not fit in 64 bits", digits)))
 }
 
-fn single_run(t: u64, d: u64) -> u64 {
-    let ways_to_win: u64 = (0..=t)
-        .into_par_iter()
-        .progress_count(t)
-        .map(|j|{
-        let race_time = t - j;
-        let travelled = j * race_time;
-        if travelled > d {
-            return 1;
-        }
-        return 0;
-    })
-    .sum();
-    ways_to_win
+fn parse_races(input: &str) -> IResult<&str, (Vec<u64>, Vec<u64>)> {
+    let (input, times) = parse_line1(input)?;
+    let (input, distances) = parse_line1(input)?;
+
+    Ok((input, (times, distances)))
 }
 
-fn race(times: &Vec<u64>, distances: &Vec<u64>) -> u64 {
-    let n = times.len() as u64;
-    let mut res = 1;
-    for i in 0..n {
-        let t = times[i as usize];
-        let d = distances[i as usize];
-        let ways_to_win = single_run(t, d);
-        res *= ways_to_win;
-    }
+fn parse_race(input: &str) -> IResult<&str, (String, String)> {
+    let (input, time) = parse_line2(input)?;
+    let (input, distance) = parse_line2(input)?;
 
-    res
+    Ok((input, (time, distance)))
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day6/input2.txt").unwrap();
-    let (input, times) = parse_line1(&input).unwrap();
-    let (_, distances) = parse_line1(input).unwrap();
-    println!("Day 6, Part 1: {}", race(&times, &distances));
+/// Counts the hold times `j` in `0..=t` for which `j * (t - j) > d`.
+///
+/// The winning hold times are the integers strictly between the roots of
+/// `j^2 - t*j + d = 0`, symmetric around `t / 2`. The integer square root gives
+/// the lower root to within one, and the exact check settles the boundary.
+/// Products are computed in `u128` so any `u64` race fits.
+fn ways_to_win(t: u64, d: u64) -> u64 {
+    let (t, d) = (t as u128, d as u128);
+    let wins = |j: u128| j * (t - j) > d;
+    if !wins(t / 2) {
+        return 0;
+    }
+    let mut lo = (t - (t * t - 4 * d).isqrt()) / 2;
+    while !wins(lo) {
+        lo += 1;
+    }
+    while lo > 0 && wins(lo - 1) {
+        lo -= 1;
+    }
+    (t - 2 * lo + 1) as u64
 }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day6/input2.txt").unwrap();
-    let (input, time) = parse_line2(&input).unwrap();
-    let (_, distance) = parse_line2(input).unwrap();
-    println!("Day 6, Part 1: {}", single_run(time, distance));
+fn race(times: &[u64], distances: &[u64]) -> Result<u64> {
+    times.iter().zip(distances).try_fold(1u64, |product, (&t, &d)| {
+        product
+            .checked_mul(ways_to_win(t, d))
+            .ok_or_else(|| Error::no_solution("the product of the ways to win does not fit in 64 bits"))
+    })
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u32 = 6;
+
+    type Model<'a> = Races;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        let (times, distances) = error::complete(input, parse_races(input))?;
+        if times.len() != distances.len() {
+            return Err(Error::parse(2, 1, format!("expected {} distances", times.len())));
+        }
+        let (time, distance) = error::complete(input, parse_race(input))?;
+        Ok(Races { times, distances, time, distance })
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(race(&model.times, &model.distances)?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(ways_to_win(concatenated(&model.time)?, concatenated(&model.distance)?).into())
+    }
 }
 
 #[cfg(test)]
@@ -80,7 +119,7 @@ mod tests {
         let input = std::fs::read_to_string("data/day6/input1.txt").unwrap();
         let (input, times) = super::parse_line1(&input).unwrap();
         let (_, distances) = super::parse_line1(input).unwrap();
-        assert_eq!(288, super::race(&times, &distances));
+        assert_eq!(288, super::race(&times, &distances).unwrap());
     }
 
     #[test]
@@ -88,7 +127,7 @@ mod tests {
         let input = std::fs::read_to_string("data/day6/input2.txt").unwrap();
         let (input, times) = super::parse_line1(&input).unwrap();
         let (_, distances) = super::parse_line1(input).unwrap();
-        assert_eq!(588588, super::race(&times, &distances));
+        assert_eq!(588588, super::race(&times, &distances).unwrap());
     }
 
     #[test]
@@ -96,7 +135,7 @@ mod tests {
         let input = std::fs::read_to_string("data/day6/input1.txt").unwrap();
         let (input, time) = super::parse_line2(&input).unwrap();
         let (_, distance) = super::parse_line2(input).unwrap();
-        assert_eq!(71503, super::single_run(time, distance));
+        assert_eq!(71503, super::ways_to_win(super::concatenated(&time).unwrap(), super::concatenated(&distance).unwrap()));
     }
 
     #[test]
@@ -104,6 +143,43 @@ mod tests {
         let input = std::fs::read_to_string("data/day6/input2.txt").unwrap();
         let (input, time) = super::parse_line2(&input).unwrap();
         let (_, distance) = super::parse_line2(input).unwrap();
-        assert_eq!(34655848, super::single_run(time, distance));
+        assert_eq!(34655848, super::ways_to_win(super::concatenated(&time).unwrap(), super::concatenated(&distance).unwrap()));
+    }
+
+    fn brute_force(t: u64, d: u64) -> u64 {
+        (0..=t).filter(|j| j * (t - j) > d).count() as u64
+    }
+
+    #[test]
+    fn t5() {
+        for t in 0..80 {
+            for d in 0..=t * t / 4 + 1 {
+                assert_eq!(brute_force(t, d), super::ways_to_win(t, d), "t={} d={}", t, d);
+            }
+        }
+        for (t, d) in [(71530, 940200), (1_000_003, 123_456_789_012), (999_999, 249_999_500_000)] {
+            assert_eq!(brute_force(t, d), super::ways_to_win(t, d), "t={} d={}", t, d);
+        }
+    }
+
+    #[test]
+    fn t6() {
+        assert_eq!(super::ways_to_win(30, 200), 9);
+        assert_eq!(super::ways_to_win(u64::MAX, 0), u64::MAX - 1);
+        assert_eq!(super::ways_to_win(u64::MAX, u64::MAX), u64::MAX - 3);
+        let half = u64::MAX / 2;
+        assert_eq!(super::ways_to_win(u64::MAX, u64::MAX), super::ways_to_win(u64::MAX, u64::MAX - 1));
+        assert_eq!(super::ways_to_win(2 * 4_000_000_000, 4_000_000_000 * 4_000_000_000), 0);
+        assert_eq!(super::ways_to_win(2 * 4_000_000_000, 4_000_000_000 * 4_000_000_000 - 1), 1);
+        assert_eq!(super::ways_to_win(half, 0), half - 1);
+    }
+
+    #[test]
+    fn t7() {
+        use crate::{Answer, Solution};
+        let races = super::Day6::parse("Time: 5000000000 5000000000\nDistance: 6249999999999999999 6249999999999999999\n").unwrap();
+        assert_eq!(super::Day6::part1(&races).unwrap(), Answer::Number(1));
+        let e = super::Day6::part2(&races).unwrap_err();
+        assert_eq!(e.to_string(), "invalid: 50000000005000000000 does not fit in 64 bits");
     }
 }
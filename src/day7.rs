@@ -1,359 +1,342 @@
 use nom::{
-    bytes::complete::tag,
     character::complete::{
+        satisfy,
         space1,
-        digit1,
+        u32,
         line_ending
     },
     multi::{
-        many1,
+        count,
         separated_list1
     },
-    branch::alt,
     IResult
 };
 
-use std::cmp::Ordering;
 use std::fmt;
 use itertools::Itertools;
+use crate::{Answer, Part, Solution};
+use crate::error::{self, Error, Result};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Card {
-    value: u8,
+const NUMBERS: [&str; 10] = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+/// A hand category, identified by the sizes of its groups of equal cards in
+/// decreasing order (a full house is `[3, 2]`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Category {
+    shape: Vec<usize>,
 }
 
-#[derive(Debug)]
-struct Hand {
-    cards: Vec<Card>,
-    bid: u32,
-    points: u32,
+impl Category {
+    /// The category for groups of these sizes, in any order.
+    pub fn new(mut shape: Vec<usize>) -> Self {
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+        Category { shape }
+    }
+
+    pub fn name(&self) -> String {
+        let word = |n: usize| NUMBERS.get(n).map_or_else(|| n.to_string(), |w| w.to_string());
+        match self.shape.as_slice() {
+            [3, 2] => "full house".to_string(),
+            [2, 2, 1] => "two pair".to_string(),
+            [2, rest @ ..] if rest.iter().all(|&n| n == 1) => "one pair".to_string(),
+            [1, ..] => "high card".to_string(),
+            [n, rest @ ..] if rest.iter().all(|&n| n == 1) => format!("{} of a kind", word(*n)),
+            shape => shape.iter().join("+"),
+        }
+    }
 }
 
-impl fmt::Display for Hand {
+impl fmt::Display for Category {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}", 
-            self.cards.iter()
-               .map(|x| symbolic_value(x.value))
-               .collect::<String>(),
-            self.bid
-        )
+        write!(f, "{}", self.name())
     }
 }
 
-fn symbolic_value(value: u8) -> String {
-    match value {
-        1 => "*".to_string(),
-        2 => "2".to_string(),
-        3 => "3".to_string(),
-        4 => "4".to_string(),
-        5 => "5".to_string(),
-        6 => "6".to_string(),
-        7 => "7".to_string(),
-        8 => "8".to_string(),
-        9 => "9".to_string(),
-        10 => "T".to_string(),
-        11 => "J".to_string(),
-        12 => "Q".to_string(),
-        13 => "K".to_string(),
-        14 => "A".to_string(),
-        _ => "X".to_string()
+/// Every way of splitting `n` cards into groups, largest group first.
+fn partitions(n: usize, max: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
     }
+    (1..=n.min(max))
+        .rev()
+        .flat_map(|first| {
+            partitions(n - first, first).into_iter().map(move |mut rest| {
+                rest.insert(0, first);
+                rest
+            })
+        })
+        .collect()
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct FiveOfAKind {
-    value: u8,
+/// How hands are read and ranked: the cards from weakest to strongest, which
+/// of them are wild, and how many cards make a hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    order: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+    categories: Vec<Category>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct FourOfAKind {
-    value: u8,
-}
+impl RuleSet {
+    /// Rules with every way of grouping `hand_size` cards as a category, ranked
+    /// by their largest group first.
+    pub fn new(order: &str, wild: &str, hand_size: usize) -> Result<Self> {
+        let order: Vec<char> = order.chars().collect();
+        let wild: Vec<char> = wild.chars().collect();
+        if !order.iter().all_unique() {
+            return Err(Error::invalid(format!("duplicate cards in {:?}", order)));
+        }
+        if let Some(c) = wild.iter().find(|c| !order.contains(c)) {
+            return Err(Error::invalid(format!("wild card {:?} is not in {:?}", c, order)));
+        }
+        if order.is_empty() || order.len() > u8::MAX as usize + 1 {
+            return Err(Error::invalid("there must be between 1 and 256 cards"));
+        }
+        if hand_size == 0 {
+            return Err(Error::invalid("hands need at least one card"));
+        }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct FullHouse {
-    three: u8,
-    two: u8,
-}
+        let mut categories: Vec<Category> = partitions(hand_size, hand_size).into_iter().map(Category::new).collect();
+        categories.sort();
+        Ok(RuleSet { order, wild, hand_size, categories })
+    }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct ThreeOfAKind {
-    value: u8,
-}
+    /// Ranks the categories as given, weakest first. Every way of grouping the
+    /// cards of a hand must appear exactly once.
+    pub fn with_categories(mut self, categories: Vec<Category>) -> Result<Self> {
+        if let Some(c) = categories.iter().find(|c| c.shape.iter().sum::<usize>() != self.hand_size) {
+            return Err(Error::invalid(format!("{} does not have {} cards", c, self.hand_size)));
+        }
+        if let Some(c) = categories.iter().duplicates().next() {
+            return Err(Error::invalid(format!("{} is ranked twice", c)));
+        }
+        if let Some(c) = self.categories.iter().find(|c| !categories.contains(c)) {
+            return Err(Error::invalid(format!("{} is not ranked", c)));
+        }
+        self.categories = categories;
+        Ok(self)
+    }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct TwoPair {
-    high: u8,
-    low: u8,
-}
+    pub fn standard() -> Self {
+        RuleSet::new("23456789TJQKA", "", 5).unwrap()
+    }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct OnePair {
-    value: u8,
+    pub fn jokers() -> Self {
+        RuleSet::new("J23456789TQKA", "J", 5).unwrap()
+    }
+
+    /// The hand categories from weakest to strongest.
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    fn value(&self, symbol: char) -> Option<u8> {
+        self.order.iter().position(|&c| c == symbol).map(|i| i as u8)
+    }
+
+    /// The card with this value, if there is one.
+    pub fn symbolic_value(&self, value: u8) -> Option<char> {
+        self.order.get(value as usize).copied()
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.symbolic_value(card.value).is_some_and(|c| self.wild.contains(&c))
+    }
+
+    /// The hand the wild cards stand in for, see [`RuleSet::substitute`].
+    fn effective(&self, cards: &[Card]) -> Vec<Card> {
+        let (_, mut values) = self.substitute(cards);
+        cards
+            .iter()
+            .map(|card| if self.is_wild(card) { Card { value: values.remove(0) } } else { *card })
+            .collect()
+    }
+
+    fn render(&self, cards: &[Card]) -> String {
+        cards.iter().filter_map(|card| self.symbolic_value(card.value)).collect()
+    }
+
+    /// Ranks the hands in `input` under these rules and reports why each one
+    /// ended up where it did, weakest first.
+    pub fn explain(&self, input: &str) -> Result<Vec<Explanation>> {
+        let mut hands = error::complete(input, parse_lines(input, self))?;
+        rank(&mut hands);
+        Ok(hands
+            .iter()
+            .enumerate()
+            .map(|(i, hand)| Explanation {
+                hand: self.render(&hand.cards),
+                effective: self.render(&self.effective(&hand.cards)),
+                category: self.categories[hand.category].name(),
+                rank: i + 1,
+                bid: hand.bid,
+                winnings: (i + 1) * hand.bid as usize,
+            })
+            .collect())
+    }
+
+    fn categorize(&self, cards: &[Card]) -> usize {
+        self.substitute(cards).0
+    }
+
+    /// The strongest category these rules allow for `cards`, and the values the
+    /// wild cards take to get there. Each wild card may join any group of
+    /// equal cards or start a new one; the first best choice wins, trying
+    /// larger (then stronger) groups first, then new groups of the strongest
+    /// unused card.
+    fn substitute(&self, cards: &[Card]) -> (usize, Vec<u8>) {
+        let mut groups: Vec<(usize, u8)> = cards
+            .iter()
+            .filter(|card| !self.is_wild(card))
+            .counts_by(|card| card.value)
+            .into_iter()
+            .map(|(value, n)| (n, value))
+            .collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        let wild = cards.len() - groups.iter().map(|&(n, _)| n).sum::<usize>();
+        let mut best = None;
+        self.assign(&mut groups, wild, &mut Vec::new(), &mut best);
+        best.unwrap()
+    }
+
+    fn assign(&self, groups: &mut Vec<(usize, u8)>, wild: usize, values: &mut Vec<u8>, best: &mut Option<(usize, Vec<u8>)>) {
+        if wild == 0 {
+            let shape = Category::new(groups.iter().map(|&(n, _)| n).collect());
+            let category = self.categories.iter().position(|c| *c == shape).unwrap();
+            if best.as_ref().is_none_or(|(best, _)| category > *best) {
+                *best = Some((category, values.clone()));
+            }
+            return;
+        }
+        for i in 0..groups.len() {
+            groups[i].0 += 1;
+            values.push(groups[i].1);
+            self.assign(groups, wild - 1, values, best);
+            values.pop();
+            groups[i].0 -= 1;
+        }
+        let unused = (0..self.order.len()).rev().map(|v| v as u8).find(|&v| groups.iter().all(|&(_, g)| g != v));
+        if let Some(value) = unused {
+            groups.push((1, value));
+            values.push(value);
+            self.assign(groups, wild - 1, values, best);
+            values.pop();
+            groups.pop();
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct HighCard {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Card {
     value: u8,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
-struct HandPoints {
-    five_of_a_kind: Option<FiveOfAKind>,
-    four_of_a_kind: Option<FourOfAKind>,
-    full_house: Option<FullHouse>,
-    three_of_a_kind: Option<ThreeOfAKind>,
-    two_pair: Option<TwoPair>,
-    one_pair: Option<OnePair>,
-    high_card: Option<HighCard>,
+#[derive(Debug, Clone)]
+struct Hand {
+    cards: Vec<Card>,
+    bid: u32,
+    category: usize,
 }
 
-fn parse_card(input: &str) -> IResult<&str, Card> {
-    let (input, value) = alt((
-        tag("2"),
-        tag("3"),
-        tag("4"),
-        tag("5"),
-        tag("6"),
-        tag("7"),
-        tag("8"),
-        tag("9"),
-        tag("T"),
-        tag("J"),
-        tag("Q"),
-        tag("K"),
-        tag("A"),
-    ))(input)?;
-
-    let value = match value {
-        "2" => 2,
-        "3" => 3,
-        "4" => 4,
-        "5" => 5,
-        "6" => 6,
-        "7" => 7,
-        "8" => 8,
-        "9" => 9,
-        "T" => 10,
-        "J" => 11,
-        "Q" => 12,
-        "K" => 13,
-        "A" => 14,
-        _ => unreachable!(),
-    };
-
-    Ok((input, Card { value }))
+pub struct Hands {
+    standard: Vec<Hand>,
+    jokers: Vec<Hand>,
 }
 
-fn parse_card2(input: &str) -> IResult<&str, Card> {
-    let (input, value) = alt((
-        tag("2"),
-        tag("3"),
-        tag("4"),
-        tag("5"),
-        tag("6"),
-        tag("7"),
-        tag("8"),
-        tag("9"),
-        tag("T"),
-        tag("J"),
-        tag("Q"),
-        tag("K"),
-        tag("A"),
-    ))(input)?;
-
-    let value = match value {
-        "2" => 2,
-        "3" => 3,
-        "4" => 4,
-        "5" => 5,
-        "6" => 6,
-        "7" => 7,
-        "8" => 8,
-        "9" => 9,
-        "T" => 10,
-        "J" => 1,
-        "Q" => 12,
-        "K" => 13,
-        "A" => 14,
-        _ => unreachable!(),
-    };
-
-    Ok((input, Card { value }))
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub hand: String,
+    pub effective: String,
+    pub category: String,
+    pub rank: usize,
+    pub bid: u32,
+    pub winnings: usize,
 }
 
-fn parse_line(input: &str) -> IResult<&str, Hand> {
-    // 32T3K 765
-    let (input, cards) = many1(parse_card)(input)?;
-    let (input, _) = space1(input)?;
-    let (input, bid) = digit1(input)?;
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {}  {:<15} {:>5} * {:<5} = {}",
+            self.hand,
+            self.effective,
+            self.category,
+            self.rank,
+            self.bid,
+            self.winnings
+        )
+    }
+}
 
-    let bid = bid.parse::<u32>().unwrap();
-    let hand = Hand { cards, bid, points: 0};
+fn parse_card<'a>(rules: &RuleSet, input: &'a str) -> IResult<&'a str, Card> {
+    let (input, symbol) = satisfy(|c| rules.order.contains(&c))(input)?;
+    let value = rules.value(symbol).unwrap();
 
-    Ok((input, hand))
+    Ok((input, Card { value }))
 }
 
-fn parse_line2(input: &str) -> IResult<&str, Hand> {
+fn parse_line<'a>(rules: &RuleSet, input: &'a str) -> IResult<&'a str, Hand> {
     // 32T3K 765
-    let (input, cards) = many1(parse_card2)(input)?;
+    let (input, cards) = count(|i| parse_card(rules, i), rules.hand_size)(input)?;
     let (input, _) = space1(input)?;
-    let (input, bid) = digit1(input)?;
+    let (input, bid) = u32(input)?;
 
-    let bid = bid.parse::<u32>().unwrap();
-    let hand = Hand { cards, bid, points: 0};
+    let category = rules.categorize(&cards);
+    let hand = Hand { cards, bid, category };
 
     Ok((input, hand))
 }
 
-fn parse_lines(input: &str) -> IResult<&str, Vec<Hand>> {
+fn parse_lines<'a>(input: &'a str, rules: &RuleSet) -> IResult<&'a str, Vec<Hand>> {
     let (input, hands) = separated_list1(
         line_ending,
-        parse_line
+        |i| parse_line(rules, i)
     )(input)?;
 
     Ok((input, hands))
 }
 
-fn parse_lines2(input: &str) -> IResult<&str, Vec<Hand>> {
-    let (input, hands) = separated_list1(
-        line_ending,
-        parse_line2
-    )(input)?;
-
-    Ok((input, hands))
+fn rank(hands: &mut [Hand]) {
+    hands.sort_by(|a, b| (a.category, &a.cards).cmp(&(b.category, &b.cards)));
 }
 
-fn compute_hands(hands: &mut Vec<Hand>) -> usize {
-    let mut total_winnings = 0;
-
-    for hand in hands.iter_mut() {
-        let values = hand.cards
-            .iter()
-            .counts_by(|x| x.value)
-            .values()
-            .sorted()
-            .map(|x| x.to_string())
-            .collect::<Vec<String>>()
-            .concat();
-        let points = match values.as_str() {
-            "11111" => 1,
-            "1112" => 2,
-            "122" => 3,
-            "113" => 4,
-            "23" => 5,
-            "14" => 6,
-            "5" => 7,
-            _ => unreachable!(),
-        };
-        hand.points = points;
-    }
-    hands.sort_by(|a, b|{
-        match b.points.cmp(&a.points) {
-            Ordering::Equal => {
-                for (x, y) in a.cards.iter().zip(b.cards.iter()) {
-                    if x.value != y.value {
-                        return y.value.cmp(&x.value);
-                    } else {
-                        continue;
-                    }
-                }
-                Ordering::Equal
-            },
-            x => x
-        }
-    });
+fn compute_hands(hands: &mut [Hand]) -> usize {
+    rank(hands);
     hands
         .iter()
-        .rev()
         .enumerate()
-        .for_each(|(i, x)| {
-            total_winnings += (i + 1) * (x.bid as usize);
-        });
+        .map(|(i, x)| (i + 1) * (x.bid as usize))
+        .sum()
+}
 
-    total_winnings
+/// Explains the ranking with the rules of the given part: standard cards for
+/// part 1, jokers for part 2.
+pub fn explain(input: &str, part: Part) -> Result<Vec<Explanation>> {
+    match part {
+        Part::One => RuleSet::standard().explain(input),
+        Part::Two => RuleSet::jokers().explain(input),
+    }
 }
 
-fn compute_hands2(hands: &mut Vec<Hand>) -> usize {
-    let mut total_winnings = 0;
+pub struct Day7;
 
-    for hand in hands.iter_mut() {
-        let values = hand.cards
-            .iter()
-            .filter(|x| x.value != 1)
-            .counts_by(|x| x.value)
-            .values()
-            .sorted()
-            .map(|x| x.to_string())
-            .collect::<Vec<String>>()
-            .concat();
-        let points = match values.as_str() {
-            // 5 cards, no jokers
-            "11111" => 1,
-            "1112" => 2, // One Pair
-            "122" => 3, // Two Pair
-            "113" => 4, // Three of a Kind
-            "23" => 5, // Full House
-            "14" => 6, // Four of a Kind
-            "5" => 7, // Five of a Kind
-            // 4 cards, 1 joker
-            "1111" => 2, // One Pair
-            "112" => 4, // Three of a Kind
-            "22" => 5, // Full House
-            "13" => 6, // Four of a Kind
-            "4" => 7, // Five of a Kind
-            // 3 cards, 2 jokers
-            "111" => 4, // Three of a Kind
-            "12" => 6, // Four of a Kind
-            "3" => 7, // Five of a Kind
-            // 2 cards, 3 jokers
-            "11" => 6, // Four of a Kind
-            "2" => 7, // Five of a Kind
-            // 1 card, 4 jokers
-            "1" => 7, // Five of a Kind
-            // 0 cards, 5 jokers
-            "" => 7, // Five of a Kind
-            _ => unreachable!(),
-        };
-        hand.points = points;
-    }
-    hands.sort_by(|a, b|{
-        match b.points.cmp(&a.points) {
-            Ordering::Equal => {
-                for (x, y) in a.cards.iter().zip(b.cards.iter()) {
-                    if x.value != y.value {
-                        return y.value.cmp(&x.value);
-                    } else {
-                        continue;
-                    }
-                }
-                Ordering::Equal
-            },
-            x => x
-        }
-    });
+impl Solution for Day7 {
+    const DAY: u32 = 7;
 
-    hands
-        .iter()
-        .rev()
-        .enumerate()
-        .for_each(|(i, x)| {
-            total_winnings += (i + 1) * (x.bid as usize);
-        });
+    type Model<'a> = Hands;
 
-    total_winnings
-}
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        let standard = error::complete(input, parse_lines(input, &RuleSet::standard()))?;
+        let jokers = error::complete(input, parse_lines(input, &RuleSet::jokers()))?;
+        Ok(Hands { standard, jokers })
+    }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day7/input2.txt").unwrap();
-    let (_, mut hands) = parse_lines(&input).unwrap();
-    println!("Day 7, Part 1: {}", compute_hands(&mut hands));
-}
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(compute_hands(&mut model.standard.clone()).into())
+    }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day7/input2.txt").unwrap();
-    let (_, mut hands) = parse_lines2(&input).unwrap();
-    println!("Day 7, Part 2: {}", compute_hands2(&mut hands));
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(compute_hands(&mut model.jokers.clone()).into())
+    }
 }
 
 #[cfg(test)]
@@ -361,28 +344,103 @@ mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day7/input1.txt").unwrap();
-        let (_, mut hands) = super::parse_lines(&input).unwrap();
+        let (_, mut hands) = super::parse_lines(&input, &super::RuleSet::standard()).unwrap();
         assert_eq!(6440, super::compute_hands(&mut hands));
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day7/input2.txt").unwrap();
-        let (_, mut hands) = super::parse_lines(&input).unwrap();
+        let (_, mut hands) = super::parse_lines(&input, &super::RuleSet::standard()).unwrap();
         assert_eq!(253954294, super::compute_hands(&mut hands));
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day7/input1.txt").unwrap();
-        let (_, mut hands) = super::parse_lines2(&input).unwrap();
-        assert_eq!(5905, super::compute_hands2(&mut hands));
+        let (_, mut hands) = super::parse_lines(&input, &super::RuleSet::jokers()).unwrap();
+        assert_eq!(5905, super::compute_hands(&mut hands));
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day7/input2.txt").unwrap();
-        let (_, mut hands) = super::parse_lines2(&input).unwrap();
-        assert_eq!(254837398, super::compute_hands2(&mut hands));
+        let (_, mut hands) = super::parse_lines(&input, &super::RuleSet::jokers()).unwrap();
+        assert_eq!(254837398, super::compute_hands(&mut hands));
+    }
+
+    #[test]
+    fn t5() {
+        use super::RuleSet;
+        let category = |rules: &RuleSet, hand: &str| {
+            let (_, hand) = super::parse_line(rules, hand).unwrap();
+            rules.categories()[hand.category].name()
+        };
+        let names: Vec<String> = RuleSet::standard().categories().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["high card", "one pair", "two pair", "three of a kind", "full house", "four of a kind", "five of a kind"]);
+
+        assert_eq!(category(&RuleSet::jokers(), "KTJJT 1"), "four of a kind");
+        assert_eq!(category(&RuleSet::jokers(), "JJJJJ 1"), "five of a kind");
+
+        let deuces = RuleSet::new("2J3456789TQKA", "2J", 5).unwrap();
+        assert_eq!(category(&deuces, "2J345 1"), "three of a kind");
+        assert_eq!(category(&deuces, "2KK33 1"), "full house");
+
+        let six = RuleSet::new("23456789TJQKA", "", 6).unwrap();
+        assert_eq!(six.categories().len(), 11);
+        assert_eq!(category(&six, "AAAAAA 1"), "six of a kind");
+        assert_eq!(category(&six, "AAAKKK 1"), "3+3");
+        assert_eq!(category(&six, "AAKKQQ 1"), "2+2+2");
+        let (_, mut hands) = super::parse_lines("AAAKKK 1\nAAAAKQ 10\n23456T 100", &six).unwrap();
+        assert_eq!(super::compute_hands(&mut hands), 100 + 2 + 30);
+    }
+
+    #[test]
+    fn t6() {
+        use crate::Part;
+        let input = std::fs::read_to_string("data/day7/input1.txt").unwrap();
+        let explanations = super::explain(&input, Part::Two).unwrap();
+        assert_eq!(explanations.iter().map(|e| e.winnings).sum::<usize>(), 5905);
+        let last = explanations.last().unwrap();
+        assert_eq!((last.hand.as_str(), last.effective.as_str(), last.category.as_str()), ("KTJJT", "KTTTT", "four of a kind"));
+        assert_eq!(last.to_string(), "KTJJT -> KTTTT  four of a kind      5 * 220   = 1100");
+        assert_eq!(super::RuleSet::jokers().explain("JJJJJ 3").unwrap()[0].effective, "AAAAA");
+    }
+
+    #[test]
+    fn t7() {
+        use super::{Category, RuleSet};
+        let shapes = [vec![1, 1, 1], vec![3], vec![2, 1]];
+        let rules = RuleSet::new("ABC", "", 3).unwrap().with_categories(shapes.map(Category::new).to_vec()).unwrap();
+        let (_, mut hands) = super::parse_lines("AAB 1\nCCC 10\nABC 100", &rules).unwrap();
+        assert_eq!(super::compute_hands(&mut hands), 100 + 2 * 10 + 3);
+        assert_eq!(rules.symbolic_value(2), Some('C'));
+        assert_eq!(rules.symbolic_value(3), None);
+
+        let e = RuleSet::new("ABC", "", 3).unwrap().with_categories(vec![Category::new(vec![1, 2]), Category::new(vec![3])]).unwrap_err();
+        assert_eq!(e.to_string(), "invalid: high card is not ranked");
+        let e = RuleSet::new("ABC", "", 3).unwrap().with_categories(vec![Category::new(vec![4])]).unwrap_err();
+        assert_eq!(e.to_string(), "invalid: four of a kind does not have 3 cards");
+        assert_eq!(RuleSet::new("ABA", "", 3).unwrap_err().to_string(), "invalid: duplicate cards in ['A', 'B', 'A']");
+        assert_eq!(RuleSet::new("ABC", "J", 3).unwrap_err().to_string(), "invalid: wild card 'J' is not in ['A', 'B', 'C']");
+        assert!(RuleSet::new("ABC", "", 0).is_err());
+    }
+
+    #[test]
+    fn t8() {
+        use super::{Category, RuleSet};
+        let shapes = [vec![3], vec![2, 1], vec![1, 1, 1]];
+        let rules = RuleSet::new("ABC", "A", 3).unwrap().with_categories(shapes.map(Category::new).to_vec()).unwrap();
+        let explanations = rules.explain("ABC 1\nABB 10\nAAA 100").unwrap();
+        let summary: Vec<_> = explanations.iter().map(|e| (e.hand.as_str(), e.effective.as_str(), e.category.as_str())).collect();
+        assert_eq!(summary, [("ABB", "CBB", "one pair"), ("AAA", "CBA", "high card"), ("ABC", "ABC", "high card")]);
+        assert_eq!(explanations.iter().map(|e| e.winnings).sum::<usize>(), 10 + 200 + 3);
+
+        let order: String = (0..256).map(|i| char::from_u32(0x100 + i).unwrap()).collect();
+        let first = order.chars().next().unwrap().to_string();
+        let rules = RuleSet::new(&order, &first, 5).unwrap();
+        let explanation = &rules.explain(&format!("{} 1", first.repeat(5))).unwrap()[0];
+        assert_eq!(explanation.effective, order.chars().last().unwrap().to_string().repeat(5));
+        assert!(RuleSet::new(&format!("{}x", order), "", 5).is_err());
     }
 }
@@ -9,13 +9,94 @@ use nom::{
         separated_list1
     },
     branch::alt,
+    combinator::value,
     IResult
 };
-use std::collections::BTreeMap;
-use num::integer::lcm;
+use std::collections::HashMap;
+use std::fmt;
+use itertools::Itertools;
+use num::integer::{ExtendedGcd, Integer};
+use petgraph::dot::Dot;
+use petgraph::graph::{DiGraph, NodeIndex};
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
 
-fn parse_directions(input: &str) -> IResult<&str, Vec<&str>> {
-    let (input, directions) = many1(alt((tag("L"), tag("R"))))(input)?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Left => write!(f, "L"),
+            Direction::Right => write!(f, "R"),
+        }
+    }
+}
+
+/// The network with every node interned: node `i` is called `names[i]` and
+/// leads to `edges[i][0]` going left and `edges[i][1]` going right.
+#[derive(Debug, Clone)]
+pub struct Network<'a> {
+    directions: Vec<Direction>,
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+    edges: Vec<[usize; 2]>,
+}
+
+impl<'a> Network<'a> {
+    fn new(input: &str, directions: Vec<Direction>, entries: Vec<(&'a str, &'a str, &'a str)>) -> Result<Self> {
+        let names: Vec<&str> = entries.iter().map(|&(name, _, _)| name).collect();
+        let ids: HashMap<&str, usize> = names.iter().enumerate().map(|(id, &name)| (name, id)).collect();
+        let id = |name: &str| {
+            ids.get(name).copied().ok_or_else(|| {
+                let rest = &input[name.as_ptr() as usize - input.as_ptr() as usize..];
+                Error::parse_at(input, rest, format!("node {} is not in the network", name))
+            })
+        };
+        let edges = entries
+            .iter()
+            .map(|&(_, left, right)| Ok([id(left)?, id(right)?]))
+            .collect::<Result<_>>()?;
+        Ok(Network { directions, names, ids, edges })
+    }
+
+    fn id(&self, name: &str) -> Result<usize> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::no_solution(format!("node {} is not in the network", name)))
+    }
+
+    fn next(&self, node: usize, direction: Direction) -> usize {
+        self.edges[node][direction as usize]
+    }
+
+    pub fn to_graph(&self) -> DiGraph<&'a str, Direction> {
+        let mut graph = DiGraph::with_capacity(self.names.len(), 2 * self.names.len());
+        for &name in &self.names {
+            graph.add_node(name);
+        }
+        for (node, &[left, right]) in self.edges.iter().enumerate() {
+            graph.add_edge(NodeIndex::new(node), NodeIndex::new(left), Direction::Left);
+            graph.add_edge(NodeIndex::new(node), NodeIndex::new(right), Direction::Right);
+        }
+        graph
+    }
+
+    /// The network in Graphviz DOT format, with edges labelled L and R.
+    pub fn to_dot(&self) -> String {
+        format!("{}", Dot::new(&self.to_graph()))
+    }
+}
+
+fn parse_directions(input: &str) -> IResult<&str, Vec<Direction>> {
+    let (input, directions) = many1(alt((
+        value(Direction::Left, tag("L")),
+        value(Direction::Right, tag("R")),
+    )))(input)?;
     let (input, _) = line_ending(input)?;
     Ok((input, directions))
 }
@@ -35,126 +116,238 @@ fn parse_entry(input: &str) -> IResult<&str, (&str, &str, &str)> {
     Ok((input, (start, left, right)))
 }
 
-fn parse_entries(input: &str) -> IResult<&str, (Vec<&str>, BTreeMap<&str, (&str, &str)>)> {
+type Entries<'a> = (Vec<Direction>, Vec<(&'a str, &'a str, &'a str)>);
+
+fn parse_entries(input: &str) -> IResult<&str, Entries<'_>> {
     let (input, directions) = parse_directions(input)?;
     let (input, _) = line_ending(input)?;
     let (input, entries) = separated_list1(line_ending, parse_entry)(input)?;
-    let mut jump_table = BTreeMap::new();
-    for (start, left, right) in entries {
-        jump_table.insert(start, (left, right));
-    }
-    Ok((input, (directions, jump_table)))
+    Ok((input, (directions, entries)))
 }
 
-fn walk(start: &str, directions: Vec<&str>, jump_table: &BTreeMap<&str, (&str, &str)>) -> usize {
-    let mut current = start;
-    let mut steps = 0;
-    loop {
-        for step in 0..directions.len() {
-            let (left, right) = jump_table.get(current).unwrap();
-            if directions[step] == "L" {
-                current = left;
-            } else {
-                current = right;
-            }
-            steps += 1;
-            if current == "ZZZ" {
-                return steps;
-            }
+fn parse(input: &str) -> Result<Network<'_>> {
+    let (directions, entries) = error::complete(input, parse_entries(input))?;
+    Network::new(input, directions, entries)
+}
+
+/// Steps from `start` to `end`. After one step per (node, direction index)
+/// state the walk must be repeating itself, so `end` is out of reach.
+fn walk(network: &Network, start: &str, end: &str) -> Result<usize> {
+    let (mut current, end) = (network.id(start)?, network.id(end)?);
+    let directions = network.directions.iter().cycle();
+    for (steps, &direction) in (1..=network.names.len() * network.directions.len()).zip(directions) {
+        current = network.next(current, direction);
+        if current == end {
+            return Ok(steps);
         }
     }
+    Err(Error::no_solution(format!("{} cannot be reached from {}", network.names[end], start)))
 }
 
-fn walk2(start: &str, directions: Vec<&str>, jump_table: &BTreeMap<&str, (&str, &str)>) -> usize {
-    let mut current = start;
-    let mut steps = 0;
-    loop {
-        for step in 0..directions.len() {
-            let (left, right) = jump_table.get(current).unwrap();
-            if directions[step] == "L" {
-                current = left;
-            } else {
-                current = right;
-            }
-            steps += 1;
-            if current.ends_with("Z") {
-                return steps;
-            }
+/// Where a walk from one start settles: after `offset` steps the (node,
+/// direction index) state starts repeating every `length` steps. `hits` are the
+/// steps before that which land on a Z node, `z_positions` the ones inside the
+/// first pass round the cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: usize,
+    pub length: usize,
+    pub hits: Vec<usize>,
+    pub z_positions: Vec<usize>,
+}
+
+impl Cycle {
+    fn contains(&self, step: usize) -> bool {
+        if step < self.offset {
+            self.hits.contains(&step)
+        } else {
+            let step = self.offset + (step - self.offset) % self.length;
+            self.z_positions.contains(&step)
         }
     }
 }
 
-fn walk3(directions: Vec<&str>, jump_table: &BTreeMap<&str, (&str, &str)>) -> usize {
-    let mut currents = Vec::new();
-    let mut steps = Vec::new();
-    for (start, (_, _)) in jump_table {
-        if start.ends_with("A") {
-            currents.push(start);
+fn find_cycle(network: &Network, start: usize) -> Cycle {
+    let n = network.directions.len();
+    let mut seen = vec![None; network.names.len() * n];
+    let mut ends = Vec::new();
+    let mut current = start;
+    for step in 0.. {
+        let index = step % n;
+        if let Some(offset) = seen[current * n + index] {
+            let (hits, z_positions) = ends.into_iter().partition(|&end| end < offset);
+            return Cycle { offset, length: step - offset, hits, z_positions };
+        }
+        seen[current * n + index] = Some(step);
+        if network.names[current].ends_with('Z') {
+            ends.push(step);
         }
+        current = network.next(current, network.directions[index]);
     }
+    unreachable!()
+}
 
-    for current in currents {
-        steps.push(walk2(current, directions.clone(), jump_table));
+/// Cycle analysis for every node ending with A.
+pub fn ghost_cycles<'a>(network: &Network<'a>) -> Result<Vec<(&'a str, Cycle)>> {
+    let starts: Vec<usize> = (0..network.names.len()).filter(|&node| network.names[node].ends_with('A')).collect();
+    if starts.is_empty() {
+        return Err(Error::no_solution("no starting node ends with A"));
     }
-
-    lcmm(steps)
+    Ok(starts
+        .into_iter()
+        .map(|start| (network.names[start], find_cycle(network, start)))
+        .collect())
 }
 
-fn lcmm(nums: Vec<usize>) -> usize {
-    if nums.len() == 1 {
-        return nums[0];
+/// Merges `x = a (mod m)` and `x = b (mod n)` into a single congruence, if they
+/// agree, for moduli that need not be coprime.
+fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
+    if (b - a) % gcd != 0 {
+        return None;
     }
-    if nums.len() == 2 {
-        return lcm(nums[0], nums[1]);
+    let modulus = m / gcd * n;
+    let k = ((b - a) / gcd * x).rem_euclid(n / gcd);
+    Some(((a + m * k).rem_euclid(modulus), modulus))
+}
+
+/// How many candidate congruences `first_common_step` keeps before giving up.
+const MAX_CONGRUENCES: usize = 1 << 20;
+
+/// The first step at which every cycle is on a Z node: either one of the
+/// steps before the cycles settle, or a solution of the congruences picked from
+/// each cycle's Z positions.
+fn first_common_step(cycles: &[Cycle]) -> Result<usize> {
+    let transient = cycles
+        .iter()
+        .flat_map(|cycle| cycle.hits.iter().copied())
+        .filter(|&step| cycles.iter().all(|cycle| cycle.contains(step)))
+        .min();
+    if let Some(step) = transient {
+        return Ok(step);
     }
 
-    return lcm(nums[0], lcmm(nums[1..].to_vec()));
+    let settled = cycles.iter().map(|cycle| cycle.offset).max().unwrap_or(0) as i128;
+    let mut congruences = vec![(0, 1)];
+    for cycle in cycles {
+        if congruences.len().saturating_mul(cycle.z_positions.len()) > MAX_CONGRUENCES {
+            return Err(Error::no_solution("too many combinations of Z positions to check"));
+        }
+        congruences = congruences
+            .into_iter()
+            .cartesian_product(&cycle.z_positions)
+            .filter_map(|(congruence, &z)| crt(congruence, (z as i128, cycle.length as i128)))
+            .collect();
+        // every merged congruence has the same modulus, so equal ones are
+        // exact duplicates
+        congruences.sort_unstable();
+        congruences.dedup();
+    }
+    congruences
+        .into_iter()
+        .map(|(a, m)| if a >= settled { a } else { a + (settled - a + m - 1) / m * m })
+        .min()
+        .map(|step| step as usize)
+        .ok_or_else(|| Error::no_solution("the ghosts never stand on Z nodes at the same time"))
 }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day8/input2.txt").unwrap();
-    let (_, data) = parse_entries(&input).unwrap();
-    let steps = walk("AAA", data.0, &data.1);
-    println!("Day 8, Part 1: {}", steps);
+fn walk3(network: &Network) -> Result<usize> {
+    let cycles: Vec<Cycle> = ghost_cycles(network)?.into_iter().map(|(_, cycle)| cycle).collect();
+    first_common_step(&cycles)
 }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day8/input2.txt").unwrap();
-    let (_, data) = parse_entries(&input).unwrap();
-    let steps = walk3(data.0, &data.1);
-    println!("Day 8, Part 2: {}", steps);
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u32 = 8;
+
+    type Model<'a> = Network<'a>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(walk(model, "AAA", "ZZZ")?.into())
+    }
+
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(walk3(model)?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day8/input1.txt").unwrap();
-        let (_, data) = super::parse_entries(&input).unwrap();
-        let steps = super::walk("AAA", data.0, &data.1);
+        let network = super::parse(&input).unwrap();
+        let steps = super::walk(&network, "AAA", "ZZZ").unwrap();
         assert_eq!(steps, 6);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day8/input2.txt").unwrap();
-        let (_, data) = super::parse_entries(&input).unwrap();
-        let steps = super::walk("AAA", data.0, &data.1);
+        let network = super::parse(&input).unwrap();
+        let steps = super::walk(&network, "AAA", "ZZZ").unwrap();
         assert_eq!(steps, 20093);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day8/input3.txt").unwrap();
-        let (_, data) = super::parse_entries(&input).unwrap();
-        let steps = super::walk3(data.0, &data.1);
+        let network = super::parse(&input).unwrap();
+        let steps = super::walk3(&network).unwrap();
         assert_eq!(steps, 6);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day8/input2.txt").unwrap();
-        let (_, data) = super::parse_entries(&input).unwrap();
-        let steps = super::walk3(data.0, &data.1);
+        let network = super::parse(&input).unwrap();
+        let steps = super::walk3(&network).unwrap();
         assert_eq!(steps, 22103062509257);
     }
+
+    const GHOSTS: &str = "L\n\n11A = (11B, 11B)\n11B = (11Z, 11Z)\n11Z = (11C, 11C)\n11C = (11B, 11B)\n\
+        22A = (22Z, 22Z)\n22Z = (22P, 22P)\n22P = (22Q, 22Q)\n22Q = (22R, 22R)\n22R = (22Z, 22Z)\n\
+        33A = (33B, 33B)\n33B = (33Z, 33Z)\n33Z = (33B, 33B)";
+
+    #[test]
+    fn t5() {
+        let input = GHOSTS.replace("        ", "");
+        let network = super::parse(&input).unwrap();
+        let cycles = super::ghost_cycles(&network).unwrap();
+        assert_eq!(cycles[0], ("11A", super::Cycle { offset: 1, length: 3, hits: vec![], z_positions: vec![2] }));
+        assert_eq!(cycles[1], ("22A", super::Cycle { offset: 1, length: 4, hits: vec![], z_positions: vec![1] }));
+
+        let first_two: Vec<_> = cycles[..2].iter().map(|(_, cycle)| cycle.clone()).collect();
+        assert_eq!(super::first_common_step(&first_two).unwrap(), 5);
+        let last_two: Vec<_> = cycles[1..].iter().map(|(_, cycle)| cycle.clone()).collect();
+        assert!(super::first_common_step(&last_two).is_err());
+
+        let many = |length: usize| super::Cycle { offset: 0, length, hits: vec![], z_positions: (0..length).step_by(2).collect() };
+        let e = super::first_common_step(&[many(1000), many(1001), many(1003), many(1007)]).unwrap_err();
+        assert_eq!(e.to_string(), "no solution: too many combinations of Z positions to check");
+        assert_eq!(super::first_common_step(&[many(1000), many(2000), many(2000)]).unwrap(), 0);
+    }
+
+    #[test]
+    fn t6() {
+        let input = std::fs::read_to_string("data/day8/input1.txt").unwrap();
+        let network = super::parse(&input).unwrap();
+        let graph = network.to_graph();
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 6));
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("0 -> 1 [ label = \"L\" ]"), "{}", dot);
+
+        let network = super::parse("L\n\nAAA = (BBB, AAA)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        let e = super::walk(&network, "AAA", "ZZZ").unwrap_err();
+        assert_eq!(e.to_string(), "no solution: ZZZ cannot be reached from AAA");
+
+        let e = super::parse("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 3, column 8: node BBB is not in the network");
+    }
 }
@@ -2,118 +2,168 @@ use nom::{
     bytes::complete::tag,
     character::complete::{
         line_ending,
-        i32
+        i64
     },
     multi::separated_list1 ,
     IResult
 };
+use crate::{Answer, Solution};
+use crate::error::{self, Error, Result};
 
-fn parse_lines(input: &str) -> IResult<&str, Vec<Vec<i32>>> {
+/// The leading diagonal of a sequence's difference table: `first[i]` is the
+/// first value of the i-th differences, stopping at the first constant row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Differences {
+    first: Vec<i64>,
+    len: usize,
+}
+
+fn overflow() -> Error {
+    Error::no_solution("value does not fit in an i64")
+}
+
+impl Differences {
+    pub fn new(sequence: &[i64]) -> Result<Self> {
+        let mut first = Vec::new();
+        let mut row = sequence.to_vec();
+        while let Some(&head) = row.first() {
+            first.push(head);
+            if row.iter().all(|&x| x == head) {
+                break;
+            }
+            row = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]).ok_or_else(overflow))
+                .collect::<Result<_>>()?;
+        }
+        Ok(Differences { first, len: sequence.len() })
+    }
+
+    /// The degree of the lowest polynomial through the sequence.
+    pub fn degree(&self) -> usize {
+        self.first.len().saturating_sub(1)
+    }
+
+    /// The polynomial at `position`, where 0 is the first value of the sequence
+    /// and negative positions come before it. Newton's forward formula keeps
+    /// this O(degree) however far away `position` is.
+    pub fn value_at(&self, position: i64) -> Result<i64> {
+        let x = position as i128;
+        let mut binomial: i128 = 1;
+        let mut value: i128 = 0;
+        for (i, &d) in self.first.iter().enumerate() {
+            if i > 0 {
+                binomial = binomial.checked_mul(x - i as i128 + 1).ok_or_else(overflow)? / i as i128;
+            }
+            value = binomial
+                .checked_mul(d as i128)
+                .and_then(|term| value.checked_add(term))
+                .ok_or_else(overflow)?;
+        }
+        i64::try_from(value).map_err(|_| overflow())
+    }
+
+    /// The value `steps` places after the last one of the sequence.
+    pub fn forward(&self, steps: u64) -> Result<i64> {
+        let position = i64::try_from(steps).ok().and_then(|steps| steps.checked_add(self.len as i64 - 1));
+        self.value_at(position.ok_or_else(overflow)?)
+    }
+
+    /// The value `steps` places before the first one of the sequence.
+    pub fn backward(&self, steps: u64) -> Result<i64> {
+        self.value_at(i64::try_from(steps).map_err(|_| overflow())?.checked_neg().ok_or_else(overflow)?)
+    }
+}
+
+fn parse_lines(input: &str) -> IResult<&str, Vec<Vec<i64>>> {
     let (input, lines) = separated_list1(
         line_ending,
         separated_list1(
             tag(" "),
-            i32
+            i64
         )
     )(input)?;
     Ok((input, lines))
 }
 
-fn consume(data: &Vec<i32>) -> i32 {
-    let mut res = Vec::new();
-    for (b, a) in data.iter().skip(1).zip(data) {
-        res.push(b - a);
-    }
-    if res.iter().any(|x| *x != 0) {
-        return res.last().unwrap() + consume(&res);
-    }
-
-    0
+fn parse(input: &str) -> Result<Vec<Differences>> {
+    error::complete(input, parse_lines(input))?
+        .iter()
+        .map(|sequence| Differences::new(sequence))
+        .collect()
 }
 
-fn start_consuming(data: &Vec<i32>) -> i32 {
-    data.last().unwrap() + consume(&data)
+fn sum(model: &[Differences], f: impl Fn(&Differences) -> Result<i64>) -> Result<i64> {
+    model.iter().try_fold(0i64, |total, d| total.checked_add(f(d)?).ok_or_else(overflow))
 }
 
+pub struct Day9;
 
-fn consume2(data: &Vec<i32>) -> i32 {
-    let mut res = Vec::new();
-    for (b, a) in data.iter().skip(1).zip(data) {
-        res.push(b - a);
-    }
-    if res.iter().any(|x| *x != 0) {
-        return res.first().unwrap() - consume2(&res);
-    }
+impl Solution for Day9 {
+    const DAY: u32 = 9;
 
-    0
-}
+    type Model<'a> = Vec<Differences>;
 
-fn start_consuming2(data: &Vec<i32>) -> i32 {
-    data.first().unwrap() - consume2(&data)
-}
+    fn parse(input: &str) -> Result<Self::Model<'_>> {
+        parse(input)
+    }
 
-pub fn part1() {
-    let input = std::fs::read_to_string("data/day9/input2.txt").unwrap();
-    let (_, data) = parse_lines(&input).unwrap();
-    let res = data
-        .iter()
-        .map(|x| start_consuming(x))
-        .sum::<i32>();
-    println!("Day 9, Part 1: {}", res);
-}
+    fn part1(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(sum(model, |d| d.forward(1))?.into())
+    }
 
-pub fn part2() {
-    let input = std::fs::read_to_string("data/day9/input2.txt").unwrap();
-    let (_, data) = parse_lines(&input).unwrap();
-    let res = data
-        .iter()
-        .map(|x| start_consuming2(x))
-        .sum::<i32>();
-    println!("Day 9, Part 2: {}", res);
+    fn part2(model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(sum(model, |d| d.backward(1))?.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
+    use super::Differences;
+
     #[test]
     fn t1() {
         let input = std::fs::read_to_string("data/day9/input1.txt").unwrap();
-        let (_, data) = super::parse_lines(&input).unwrap();
-        let res = data
-            .iter()
-            .map(|x| super::start_consuming(x))
-            .sum::<i32>();
-        assert_eq!(res, 114);
+        let data = super::parse(&input).unwrap();
+        assert_eq!(super::sum(&data, |d| d.forward(1)).unwrap(), 114);
     }
 
     #[test]
     fn t2() {
         let input = std::fs::read_to_string("data/day9/input2.txt").unwrap();
-        let (_, data) = super::parse_lines(&input).unwrap();
-        let res = data
-            .iter()
-            .map(|x| super::start_consuming(x))
-            .sum::<i32>();
-        assert_eq!(res, 1901217887);
+        let data = super::parse(&input).unwrap();
+        assert_eq!(super::sum(&data, |d| d.forward(1)).unwrap(), 1901217887);
     }
 
     #[test]
     fn t3() {
         let input = std::fs::read_to_string("data/day9/input1.txt").unwrap();
-        let (_, data) = super::parse_lines(&input).unwrap();
-        let res = data
-            .iter()
-            .map(|x| super::start_consuming2(x))
-            .sum::<i32>();
-        assert_eq!(res, 2);
+        let data = super::parse(&input).unwrap();
+        assert_eq!(super::sum(&data, |d| d.backward(1)).unwrap(), 2);
     }
 
     #[test]
     fn t4() {
         let input = std::fs::read_to_string("data/day9/input2.txt").unwrap();
-        let (_, data) = super::parse_lines(&input).unwrap();
-        let res = data
-            .iter()
-            .map(|x| super::start_consuming2(x))
-            .sum::<i32>();
-        assert_eq!(res, 905);
+        let data = super::parse(&input).unwrap();
+        assert_eq!(super::sum(&data, |d| d.backward(1)).unwrap(), 905);
+    }
+
+    #[test]
+    fn t5() {
+        let d = Differences::new(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(d.degree(), 3);
+        assert_eq!((d.forward(1).unwrap(), d.backward(1).unwrap()), (68, 5));
+        let squares = Differences::new(&[0, 1, 4, 9]).unwrap();
+        assert_eq!(squares.degree(), 2);
+        assert_eq!(squares.forward(1_000_000 - 3).unwrap(), 1_000_000 * 1_000_000);
+        assert_eq!(squares.backward(7).unwrap(), 49);
+        assert_eq!(Differences::new(&[7]).unwrap().degree(), 0);
+        assert_eq!(Differences::new(&[7, 7, 7]).unwrap().forward(100).unwrap(), 7);
+
+        assert!(Differences::new(&[i64::MIN, i64::MAX]).is_err());
+        let line = Differences::new(&[0, i64::MAX / 2]).unwrap();
+        assert_eq!(line.forward(1).unwrap(), i64::MAX - 1);
+        assert!(line.forward(2).is_err());
     }
 }
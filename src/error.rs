@@ -0,0 +1,111 @@
+use nom::IResult;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        input: String,
+        error: std::io::Error,
+    },
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    NoSolution(String),
+    Invalid(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Error::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn parse_at(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        let offset = input.len().saturating_sub(rest.len());
+        let consumed = &input[..offset];
+        let line = consumed.matches('\n').count() + 1;
+        let column = match consumed.rfind('\n') {
+            Some(newline) => consumed[newline + 1..].chars().count() + 1,
+            None => consumed.chars().count() + 1,
+        };
+        Error::parse(line, column, message)
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> Self {
+        Error::NoSolution(message.into())
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Error::Invalid(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { input, error } => write!(f, "cannot read {}: {}", input, error),
+            Error::Parse { line, column, message } => write!(f, "parse error at line {}, column {}: {}", line, column, message),
+            Error::NoSolution(message) => write!(f, "no solution: {}", message),
+            Error::Invalid(message) => write!(f, "invalid: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+pub fn complete<'a, T>(input: &'a str, result: IResult<&'a str, T>) -> Result<T> {
+    match result {
+        Ok((rest, value)) => {
+            if rest.trim().is_empty() {
+                Ok(value)
+            } else {
+                Err(Error::parse_at(input, rest, "unexpected input"))
+            }
+        },
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            Err(Error::parse_at(input, e.input, format!("expected {}", e.code.description())))
+        },
+        Err(nom::Err::Incomplete(_)) => Err(Error::parse_at(input, "", "unexpected end of input")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn t1() {
+        let input = "ab\ncde\nf";
+        match Error::parse_at(input, &input[5..], "oops") {
+            Error::Parse { line, column, .. } => assert_eq!((line, column), (2, 3)),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn t2() {
+        let input = "12 x";
+        let result = nom::character::complete::u32::<&str, nom::error::Error<&str>>(input);
+        let e = super::complete(input, result).unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 1, column 3: unexpected input");
+
+        let input = "x";
+        let result = nom::character::complete::u32::<&str, nom::error::Error<&str>>(input);
+        let e = super::complete(input, result).unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 1, column 1: expected Digit");
+    }
+}
@@ -0,0 +1,198 @@
+use num::integer::gcd;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The step this direction takes, with `y` growing upwards.
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+/// A closed polygon with integer vertices; the last vertex joins back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`: positive when the
+/// three points turn counterclockwise.
+fn cross(a: Point, b: Point, c: Point) -> i128 {
+    let (abx, aby) = ((b.x - a.x) as i128, (b.y - a.y) as i128);
+    let (acx, acy) = ((c.x - a.x) as i128, (c.y - a.y) as i128);
+    abx * acy - aby * acx
+}
+
+fn on_segment(p: Point, (a, b): (Point, Point)) -> bool {
+    cross(a, b, p) == 0 && p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+fn segments_intersect((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let (d1, d2) = (cross(c, d, a).signum(), cross(c, d, b).signum());
+    let (d3, d4) = (cross(a, b, c).signum(), cross(a, b, d).signum());
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+    on_segment(a, (c, d)) || on_segment(b, (c, d)) || on_segment(c, (a, b)) || on_segment(d, (a, b))
+}
+
+impl Polygon {
+    pub fn new(mut vertices: Vec<Point>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    /// Walks the instructions from the origin, one vertex per instruction.
+    pub fn from_instructions(instructions: impl IntoIterator<Item = (Direction, i64)>) -> Self {
+        let mut position = Point::new(0, 0);
+        let mut vertices = vec![position];
+        for (direction, distance) in instructions {
+            let (dx, dy) = direction.delta();
+            position = Point::new(position.x + dx * distance, position.y + dy * distance);
+            vertices.push(position);
+        }
+        Polygon::new(vertices)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices.iter().copied().zip(self.vertices.iter().copied().cycle().skip(1))
+    }
+
+    /// Twice the signed area from the shoelace formula, so it stays an integer.
+    pub fn double_signed_area(&self) -> i64 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.double_signed_area().abs() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| ((b.x - a.x) as f64).hypot((b.y - a.y) as f64)).sum()
+    }
+
+    /// Lattice points on the edges.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|(a, b)| gcd(b.x - a.x, b.y - a.y)).sum()
+    }
+
+    /// Lattice points strictly inside, from Pick's theorem:
+    /// area = interior + boundary/2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.double_signed_area().abs() - self.boundary_points() + 2) / 2
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        match self.double_signed_area() {
+            0 => Orientation::Degenerate,
+            area if area > 0 => Orientation::CounterClockwise,
+            _ => Orientation::Clockwise,
+        }
+    }
+
+    /// Whether any two edges touch other than neighbours sharing their
+    /// common vertex.
+    pub fn is_self_intersecting(&self) -> bool {
+        let edges: Vec<(Point, Point)> = self.edges().collect();
+        let n = edges.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                if !adjacent {
+                    if segments_intersect(edges[i], edges[j]) {
+                        return true;
+                    }
+                    continue;
+                }
+                // Neighbours only meet at their shared vertex unless one
+                // doubles back along the other.
+                let (a, b) = if j == i + 1 { (edges[i], edges[j]) } else { (edges[j], edges[i]) };
+                let (u, v) = ((a.1.x - a.0.x, a.1.y - a.0.y), (b.1.x - b.0.x, b.1.y - b.0.y));
+                if cross(a.0, a.1, b.1) == 0 && u.0 * v.0 + u.1 * v.1 < 0 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Orientation, Point, Polygon};
+
+    #[test]
+    fn t1() {
+        let square = Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(4, 4), Point::new(0, 4)]);
+        assert_eq!(square.area(), 16.0);
+        assert_eq!(square.perimeter(), 16.0);
+        assert_eq!((square.boundary_points(), square.interior_points()), (16, 9));
+        assert_eq!(square.orientation(), Orientation::CounterClockwise);
+        assert!(!square.is_self_intersecting());
+
+        let triangle = Polygon::new(vec![Point::new(0, 0), Point::new(0, 3), Point::new(4, 0), Point::new(0, 0)]);
+        assert_eq!(triangle.vertices().len(), 3);
+        assert_eq!((triangle.area(), triangle.perimeter()), (6.0, 12.0));
+        assert_eq!((triangle.boundary_points(), triangle.interior_points()), (8, 3));
+        assert_eq!(triangle.orientation(), Orientation::Clockwise);
+    }
+
+    #[test]
+    fn t2() {
+        let instructions = [(Direction::Right, 2), (Direction::Down, 2), (Direction::Left, 2), (Direction::Up, 2)];
+        let polygon = Polygon::from_instructions(instructions);
+        assert_eq!(polygon.vertices(), &[Point::new(0, 0), Point::new(2, 0), Point::new(2, -2), Point::new(0, -2)]);
+        assert_eq!(polygon.orientation(), Orientation::Clockwise);
+        assert_eq!(polygon.interior_points() + polygon.boundary_points(), 9);
+    }
+
+    #[test]
+    fn t3() {
+        let bowtie = Polygon::new(vec![Point::new(0, 0), Point::new(2, 2), Point::new(2, 0), Point::new(0, 2)]);
+        assert!(bowtie.is_self_intersecting());
+        let touching = Polygon::new(vec![
+            Point::new(0, 0), Point::new(4, 0), Point::new(4, 2), Point::new(2, 0), Point::new(2, 4), Point::new(0, 4),
+        ]);
+        assert!(touching.is_self_intersecting());
+        let backtrack = Polygon::from_instructions([(Direction::Right, 3), (Direction::Left, 1), (Direction::Up, 1), (Direction::Left, 2)]);
+        assert!(backtrack.is_self_intersecting());
+        let line = Polygon::new(vec![Point::new(0, 0), Point::new(3, 0)]);
+        assert_eq!(line.orientation(), Orientation::Degenerate);
+    }
+}
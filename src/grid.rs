@@ -0,0 +1,242 @@
+use crate::error::{Error, Result};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+const OFFSETS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const OFFSETS8: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), rows * cols, "a {}x{} grid needs {} cells", rows, cols, rows * cols);
+        Grid { rows, cols, cells }
+    }
+
+    pub fn from_elem(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid::new(rows, cols, vec![value; rows * cols])
+    }
+
+    pub fn parse_with(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Self> {
+        let cols = input.lines().next().map_or(0, |line| line.chars().count());
+        if cols == 0 {
+            return Err(Error::parse(1, 1, "expected at least one column"));
+        }
+        let mut cells = Vec::new();
+        let mut rows = 0;
+        for (row, line) in input.lines().enumerate() {
+            let mut n = 0;
+            for (col, c) in line.chars().enumerate() {
+                if col == cols {
+                    return Err(Error::parse(row + 1, col + 1, format!("expected {} columns", cols)));
+                }
+                cells.push(f(c).ok_or_else(|| Error::parse(row + 1, col + 1, format!("unexpected character '{}'", c)))?);
+                n += 1;
+            }
+            if n != cols {
+                return Err(Error::parse(row + 1, n + 1, format!("expected {} columns", cols)));
+            }
+            rows += 1;
+        }
+        Ok(Grid::new(rows, cols, cells))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            Some(&self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.rows && col < self.cols {
+            Some(&mut self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn offset(&self, row: usize, col: usize, d_row: isize, d_col: isize) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(d_row).filter(|&row| row < self.rows)?;
+        let col = col.checked_add_signed(d_col).filter(|&col| col < self.cols)?;
+        Some((row, col))
+    }
+
+    pub fn neighbours4(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS4.iter().filter_map(move |&(d_row, d_col)| self.offset(row, col, d_row, d_col))
+    }
+
+    pub fn neighbours8(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS8.iter().filter_map(move |&(d_row, d_col)| self.offset(row, col, d_row, d_col))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn col(&self, col: usize) -> impl Iterator<Item = &T> + Clone {
+        assert!(col < self.cols, "column {} is outside a {}x{} grid", col, self.rows, self.cols);
+        self.cells[col..].iter().step_by(self.cols)
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> + Clone {
+        (0..self.rows).map(move |row| self.row(row))
+    }
+
+    pub fn iter_cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + Clone> + Clone {
+        (0..self.cols).map(move |col| self.col(col))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells.iter().enumerate().map(move |(i, cell)| ((i / self.cols, i % self.cols), cell))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.rows, self.cols, self.cells.iter().map(f).collect())
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..self.cols).flat_map(|col| self.col(col).cloned()).collect();
+        Grid::new(self.cols, self.rows, cells)
+    }
+
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..self.cols)
+            .flat_map(|col| (0..self.rows).rev().map(move |row| self[(row, col)].clone()))
+            .collect();
+        Grid::new(self.cols, self.rows, cells)
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..self.cols)
+            .rev()
+            .flat_map(|col| (0..self.rows).map(move |row| self[(row, col)].clone()))
+            .collect();
+        Grid::new(self.cols, self.rows, cells)
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(input: &str) -> Result<Self> {
+        Grid::parse_with(input, Some)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col).unwrap_or_else(|| panic!("({}, {}) is outside a {}x{} grid", row, col, self.rows, self.cols))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        let (rows, cols) = (self.rows, self.cols);
+        self.get_mut(row, col).unwrap_or_else(|| panic!("({}, {}) is outside a {}x{} grid", row, col, rows, cols))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.iter_rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+
+    #[test]
+    fn t1() {
+        let grid = Grid::parse("ab\ncd\nef\n").unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.to_string(), "ab\ncd\nef\n");
+
+        let e = Grid::parse("ab\nc\n").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 2, column 2: expected 2 columns");
+        let e = Grid::parse_with("..\n.x\n", |c| (c == '.').then_some(0)).unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 2, column 2: unexpected character 'x'");
+    }
+
+    #[test]
+    fn t2() {
+        let grid = Grid::from_elem(3, 4, 0);
+        assert_eq!(grid.neighbours4(0, 0).collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours4(1, 1).count(), 4);
+        assert_eq!(grid.neighbours8(2, 3).collect::<Vec<_>>(), vec![(1, 2), (1, 3), (2, 2)]);
+        assert_eq!(grid.neighbours8(1, 1).count(), 8);
+        assert_eq!(grid.offset(0, 3, 0, 1), None);
+    }
+
+    #[test]
+    fn t3() {
+        let grid = Grid::parse("abc\ndef\n").unwrap();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+    }
+
+    #[test]
+    fn t4() {
+        let grid = Grid::parse("abc\ndef\n").unwrap();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.col(2).collect::<String>(), "cf");
+        assert_eq!(grid.iter_cols().map(|col| col.collect::<String>()).collect::<Vec<_>>(), vec!["ad", "be", "cf"]);
+        assert_eq!(grid.indexed_iter().nth(4), Some(((1, 1), &'e')));
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).row(0), &['A', 'B', 'C']);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 is outside a 2x3 grid")]
+    fn t5() {
+        let grid = Grid::parse("abc\ndef\n").unwrap();
+        let _ = grid.col(3);
+    }
+
+    #[test]
+    fn t6() {
+        let grid: Grid<char> = Grid::new(2, 0, vec![]);
+        assert_eq!(grid.iter_rows().collect::<Vec<_>>(), vec![&[] as &[char], &[]]);
+        assert_eq!(grid.iter_cols().count(), 0);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::error::{Error, Result};
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Path(PathBuf),
+    Stdin,
+    Text(String),
+    Example(u32),
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Input::Example(2)
+    }
+}
+
+impl Input {
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            Input::Stdin
+        } else {
+            Input::Path(PathBuf::from(arg))
+        }
+    }
+
+    pub fn path(&self, day: u32) -> Option<PathBuf> {
+        match self {
+            Input::Path(path) => Some(path.clone()),
+            Input::Example(n) => Some(example_path(day, *n)),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self, day: u32) -> String {
+        match self {
+            Input::Stdin => "<stdin>".to_string(),
+            Input::Text(_) => "<text>".to_string(),
+            _ => self.path(day).unwrap().display().to_string(),
+        }
+    }
+
+    pub fn read(&self, day: u32) -> Result<String> {
+        let _span = tracing::info_span!("read", day).entered();
+        match self {
+            Input::Stdin => {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input).map_err(|error| Error::Io {
+                    input: self.name(day),
+                    error,
+                })?;
+                Ok(input)
+            },
+            Input::Text(text) => Ok(text.clone()),
+            Input::Path(_) | Input::Example(_) => {
+                std::fs::read_to_string(self.path(day).unwrap()).map_err(|error| Error::Io {
+                    input: self.name(day),
+                    error,
+                })
+            },
+        }
+    }
+}
+
+pub fn example_path(day: u32, n: u32) -> PathBuf {
+    PathBuf::from(format!("data/day{}/input{}.txt", day, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Input;
+
+    #[test]
+    fn t1() {
+        assert_eq!(Input::from_arg("-"), Input::Stdin);
+        assert_eq!(Input::from_arg("data/day1/input1.txt"), Input::Path("data/day1/input1.txt".into()));
+        assert_eq!(Input::Example(3).name(7), "data/day7/input3.txt");
+        assert_eq!(Input::Stdin.name(7), "<stdin>");
+    }
+
+    #[test]
+    fn t2() {
+        let input = Input::Example(1).read(1).unwrap();
+        assert_eq!(input, std::fs::read_to_string("data/day1/input1.txt").unwrap());
+        assert_eq!(Input::Text("1abc2".to_string()).read(1).unwrap(), "1abc2");
+        let e = Input::Example(99).read(1).unwrap_err();
+        assert!(e.to_string().starts_with("cannot read data/day1/input99.txt: "));
+    }
+}
@@ -0,0 +1,199 @@
+pub mod answers;
+pub mod cycle;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod error;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod timing;
+
+use error::Result;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Unimplemented,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Unimplemented => write!(f, "not implemented"),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        // usize is at most 64 bits on every supported target
+        Answer::Number(value as i128)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn phase(self) -> &'static str {
+        match self {
+            Part::One => "part1",
+            Part::Two => "part2",
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+pub trait Solution {
+    const DAY: u32;
+
+    type Model<'a>;
+
+    fn parse(input: &str) -> Result<Self::Model<'_>>;
+
+    fn part1(model: &Self::Model<'_>) -> Result<Answer>;
+
+    fn part2(_model: &Self::Model<'_>) -> Result<Answer> {
+        Ok(Answer::Unimplemented)
+    }
+}
+
+pub type Solver = fn(&str, &[Part]) -> Result<Vec<Result<Answer>>>;
+
+pub fn run<S: Solution>(input: &str, parts: &[Part]) -> Result<Vec<Result<Answer>>> {
+    let model = tracing::info_span!("parse", day = S::DAY).in_scope(|| S::parse(input))?;
+    Ok(parts
+        .iter()
+        .map(|part| match part {
+            Part::One => tracing::info_span!("part1", day = S::DAY).in_scope(|| S::part1(&model)),
+            Part::Two => tracing::info_span!("part2", day = S::DAY).in_scope(|| S::part2(&model)),
+        })
+        .collect())
+}
+
+pub fn solver(day: u32) -> Option<Solver> {
+    let solver: Solver = match day {
+        1 => run::<day1::Day1>,
+        2 => run::<day2::Day2>,
+        3 => run::<day3::Day3>,
+        4 => run::<day4::Day4>,
+        5 => run::<day5::Day5>,
+        6 => run::<day6::Day6>,
+        7 => run::<day7::Day7>,
+        8 => run::<day8::Day8>,
+        9 => run::<day9::Day9>,
+        10 => run::<day10::Day10>,
+        11 => run::<day11::Day11>,
+        12 => run::<day12::Day12>,
+        13 => run::<day13::Day13>,
+        14 => run::<day14::Day14>,
+        15 => run::<day15::Day15>,
+        16 => run::<day16::Day16>,
+        17 => run::<day17::Day17>,
+        18 => run::<day18::Day18>,
+        19 => run::<day19::Day19>,
+        _ => return None,
+    };
+    Some(solver)
+}
+
+pub fn solve(day: u32, parts: &[Part], input: &str) -> Result<Vec<Result<Answer>>> {
+    match solver(day) {
+        Some(solver) => solver(input, parts),
+        None => Ok(parts.iter().map(|_| Ok(Answer::Unimplemented)).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Answer, Part};
+
+    #[test]
+    fn t1() {
+        let input = std::fs::read_to_string("data/day1/input1.txt").unwrap();
+        let answers = super::solve(1, &[Part::One], &input).unwrap();
+        assert_eq!(answers[0].as_ref().unwrap(), &Answer::Number(142));
+    }
+
+    #[test]
+    fn t2() {
+        let input = std::fs::read_to_string("data/day19/input1.txt").unwrap();
+        let answers = super::solve(19, &[Part::One, Part::Two], &input).unwrap();
+        assert_eq!(answers[0].as_ref().unwrap(), &Answer::Number(19114));
+        assert_eq!(answers[1].as_ref().unwrap(), &Answer::Unimplemented);
+    }
+
+    #[test]
+    fn t3() {
+        assert!(super::solver(25).is_none());
+        let answers = super::solve(25, &[Part::Two], "").unwrap();
+        assert_eq!(answers[0].as_ref().unwrap(), &Answer::Unimplemented);
+    }
+
+    #[test]
+    fn t4() {
+        let e = super::solve(2, &[Part::One], "Game 1: 3 purple").unwrap_err();
+        assert_eq!(e.to_string(), "parse error at line 1, column 9: unexpected input");
+    }
+
+    #[test]
+    fn t5() {
+        assert_eq!(Answer::from(u64::MAX), Answer::Number(18446744073709551615));
+        assert_eq!(Answer::from(usize::MAX).to_string(), usize::MAX.to_string());
+        assert_eq!(Answer::from(i64::MIN), Answer::Number(i64::MIN.into()));
+    }
+}
@@ -1,112 +1,382 @@
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-
-use clap::Parser;
+use aoc23::answers::{self, Verdict};
+use aoc23::input::Input;
+use aoc23::timing::{Timing, Timings, PHASES};
+use aoc23::{Answer, Part};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+const DAYS: RangeInclusive<u32> = 1..=25;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Parts {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+    Both,
+}
+
+impl Parts {
+    fn parts(self) -> Vec<Part> {
+        match self {
+            Parts::One => vec![Part::One],
+            Parts::Two => vec![Part::Two],
+            Parts::Both => vec![Part::One, Part::Two],
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct Record {
+    day: u32,
+    part: u32,
+    status: &'static str,
+    answer: Option<i128>,
+    error: Option<String>,
+    input: String,
+    /// Time spent in this part alone; reading and parsing the input are shared
+    /// by both parts and only reported by `--time`.
+    duration_ms: f64,
+}
+
+impl Record {
+    fn new(day: u32, part: Part, input: String, result: Result<Answer, String>, duration: Duration) -> Self {
+        let (status, answer, error) = match result {
+            Ok(Answer::Number(n)) => ("ok", Some(n), None),
+            Ok(Answer::Unimplemented) => ("unimplemented", None, None),
+            Err(e) => ("error", None, Some(e)),
+        };
+        Record {
+            day,
+            part: match part {
+                Part::One => 1,
+                Part::Two => 2,
+            },
+            status,
+            answer,
+            error,
+            input,
+            duration_ms: duration.as_micros() as f64 / 1000.0,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check every solver against the answers stored in data/answers.toml
+    Verify {
+        /// File with the expected answers
+        #[arg(long, default_value = "data/answers.toml")]
+        answers: PathBuf,
+
+        /// Only verify this day, or a range of days like `3..=10`
+        #[arg(short, long, value_parser = parse_days)]
+        day: Option<RangeInclusive<u32>>,
+    },
+}
 
 #[derive(Parser)]
 struct Args {
-    #[arg(short, long, default_value_t = 1)]
-    day: u32,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Day to run, or a range of days like `3..=10`
+    #[arg(short, long, default_value = "1", value_parser = parse_days)]
+    day: RangeInclusive<u32>,
+
+    /// Run every day of the calendar
+    #[arg(short, long, conflicts_with = "day")]
+    all: bool,
+
+    #[arg(short, long, value_enum, default_value_t = Parts::Both)]
+    part: Parts,
+
+    /// Read the puzzle input from a file, or from stdin with `-`
+    #[arg(short, long, conflicts_with_all = ["example", "text"])]
+    input: Option<String>,
+
+    /// Use data/dayN/inputN.txt as puzzle input
+    #[arg(short, long, conflicts_with = "text")]
+    example: Option<u32>,
+
+    /// Pass the puzzle input inline
+    #[arg(short, long)]
+    text: Option<String>,
+
+    /// Print answers as text, or as JSON/CSV records with the time each part
+    /// took, not counting reading and parsing the input
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Print how long reading, parsing and each part took, slowest first
+    #[arg(long)]
+    time: bool,
+
+    /// Log spans and events at this level or above to stderr
+    #[arg(long, default_value_t = LevelFilter::WARN)]
+    log_level: LevelFilter,
+
+    /// Fail if all days together take longer than this many seconds
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    budget: Option<Duration>,
+
+    /// Show how day 7 ranks each hand instead of printing the answers
+    #[arg(long)]
+    explain: bool,
+}
+
+impl Args {
+    fn days(&self) -> RangeInclusive<u32> {
+        if self.all {
+            DAYS
+        } else {
+            self.day.clone()
+        }
+    }
+
+    fn input(&self) -> Input {
+        if let Some(arg) = &self.input {
+            Input::from_arg(arg)
+        } else if let Some(n) = self.example {
+            Input::Example(n)
+        } else if let Some(text) = &self.text {
+            Input::Text(text.clone())
+        } else {
+            Input::default()
+        }
+    }
+}
+
+fn parse_seconds(arg: &str) -> Result<Duration, String> {
+    let seconds = arg.parse::<f64>().map_err(|e| format!("invalid duration `{}`: {}", arg, e))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("invalid duration `{}`: {}", arg, e))
+}
+
+fn parse_days(arg: &str) -> Result<RangeInclusive<u32>, String> {
+    let parse = |s: &str| s.trim().parse::<u32>().map_err(|e| format!("invalid day `{}`: {}", s, e));
+    let days = if let Some((start, end)) = arg.split_once("..=") {
+        parse(start)?..=parse(end)?
+    } else if let Some((start, end)) = arg.split_once("..") {
+        parse(start)?..=parse(end)?.saturating_sub(1)
+    } else {
+        let day = parse(arg)?;
+        day..=day
+    };
+    if days.is_empty() || !DAYS.contains(days.start()) || !DAYS.contains(days.end()) {
+        return Err(format!("days must be within {}..={}", DAYS.start(), DAYS.end()));
+    }
+    Ok(days)
 }
 
-fn main() {
+fn run(day: u32, parts: &[Part], input: &Input, timings: &Timings) -> Vec<Record> {
+    let answers = match aoc23::solver(day) {
+        Some(solver) => input
+            .read(day)
+            .and_then(|input| solver(&input, parts))
+            .map_err(|e| e.to_string()),
+        None => Ok(parts.iter().map(|_| Ok(Answer::Unimplemented)).collect()),
+    };
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, &part)| {
+            let result = match &answers {
+                Ok(answers) => answers[i].as_ref().map(Clone::clone).map_err(|e| e.to_string()),
+                Err(e) => Err(e.clone()),
+            };
+            Record::new(day, part, input.name(day), result, timings.duration(day, part.phase()))
+        })
+        .collect()
+}
+
+fn print_timings(mut timings: Vec<Timing>) {
+    timings.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+    eprintln!("{:>4}  {:<6} {:>12}", "Day", "Phase", "Duration");
+    for timing in &timings {
+        eprintln!("{:>4}  {:<6} {:>12.3?}", timing.day, timing.phase, timing.duration);
+    }
+    let total: Duration = timings.iter().map(|timing| timing.duration).sum();
+    eprintln!("{:>4}  {:<6} {:>12.3?}", "", "total", total);
+}
+
+fn print_text(records: &[Record]) {
+    for record in records {
+        match (&record.answer, &record.error) {
+            (_, Some(e)) => eprintln!("Day {}, Part {}: {}", record.day, record.part, e),
+            (Some(n), None) => println!("Day {}, Part {}: {}", record.day, record.part, n),
+            (None, None) => println!("Day {}, Part {}: {}", record.day, record.part, Answer::Unimplemented),
+        }
+    }
+}
+
+fn print_csv(records: &[Record]) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn explain(days: RangeInclusive<u32>, parts: &[Part], input: &Input) -> ExitCode {
+    if days != (7..=7) {
+        eprintln!("--explain is only available for day 7");
+        return ExitCode::FAILURE;
+    }
+    let text = match input.read(7) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    for &part in parts {
+        match aoc23::day7::explain(&text, part) {
+            Ok(explanations) => {
+                println!("Day 7, Part {}:", part);
+                for explanation in explanations {
+                    println!("  {}", explanation);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn verify(answers: &Path, days: Option<RangeInclusive<u32>>) -> ExitCode {
+    let expected = match answers::load(answers) {
+        Ok(expected) => expected,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (mut passed, mut failed, mut regressions) = (0, 0, 0);
+    for expected in expected.iter().filter(|e| days.as_ref().is_none_or(|days| days.contains(&e.day))) {
+        for (part, verdict) in expected.verify() {
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Regression { .. } => regressions += 1,
+                Verdict::Fail(_) => failed += 1,
+            }
+            println!("Day {}, {}, Part {}: {}", expected.day, expected.input, part, verdict);
+        }
+    }
+    println!("{} passed, {} failed, {} regressions", passed, failed, regressions);
+    if failed + regressions > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
-    match args.day {
-        1 => {
-            day1::part1();
-            day1::part2();
-        },
-        2 => {
-            day2::part1();
-            day2::part2();
-        },
-        3 => {
-            day3::part1();
-            day3::part2();
-        },
-        4 => {
-            day4::part1();
-            day4::part2();
-        },
-        5 => {
-            day5::part1();
-            day5::part2();
-        },
-        6 => {
-            day6::part1();
-            day6::part2();
-        },
-        7 => {
-            day7::part1();
-            day7::part2();
-        },
-        8 => {
-            day8::part1();
-            day8::part2();
-        },
-        9 => {
-            day9::part1();
-            day9::part2();
-        },
-        10 => {
-            day10::part1();
-            day10::part2();
-        },
-        11 => {
-            day11::part1();
-            day11::part2();
-        },
-        12 => {
-            day12::part1();
-            day12::part2();
-        },
-        13 => {
-            day13::part1();
-            day13::part2();
-        },
-        14 => {
-            day14::part1();
-            day14::part2();
-        },
-        15 => {
-            day15::part1();
-            day15::part2();
-        },
-        16 => {
-            day16::part1();
-            day16::part2();
-        },
-        17 => {
-            day17::part1();
-            day17::part2();
-        },
-        18 => {
-            day18::part1();
-            day18::part2();
-        },
-        19 => {
-            day19::part1();
-        },
-        _ => {
-            unimplemented!();
+    let timings = Timings::default();
+    tracing_subscriber::registry()
+        .with(timings.clone().with_filter(filter_fn(|metadata| metadata.is_span() && PHASES.contains(&metadata.name()))))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr).with_filter(args.log_level))
+        .init();
+    if let Some(Command::Verify { answers, day }) = args.command {
+        return verify(&answers, day);
+    }
+
+    let parts = args.part.parts();
+    let input = args.input();
+    let days = args.days();
+    if matches!(input, Input::Stdin | Input::Text(_)) && days.start() != days.end() {
+        eprintln!("stdin and --text hold a single day's input, pick one day");
+        return ExitCode::FAILURE;
+    }
+    if args.explain {
+        return explain(args.days(), &parts, &input);
+    }
+    let mut records = Vec::new();
+    for day in args.days() {
+        records.extend(run(day, &parts, &input, &timings));
+    }
+    match args.format {
+        Format::Text => print_text(&records),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        Format::Csv => {
+            if let Err(e) = print_csv(&records) {
+                eprintln!("cannot write csv: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if args.time {
+        print_timings(timings.snapshot());
+    }
+    let mut failed = records.iter().any(|record| record.error.is_some());
+    if let Some(budget) = args.budget {
+        let total: Duration = timings.snapshot().iter().map(|timing| timing.duration).sum();
+        if total > budget {
+            eprintln!("total time {:.3?} exceeds the budget of {:.3?}", total, budget);
+            failed = true;
         }
     }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use aoc23::{Answer, Part};
+    use std::time::Duration;
+
+    #[test]
+    fn t1() {
+        assert_eq!(super::parse_days("7"), Ok(7..=7));
+        assert_eq!(super::parse_days("3..=10"), Ok(3..=10));
+        assert_eq!(super::parse_days("3..10"), Ok(3..=9));
+    }
+
+    #[test]
+    fn t2() {
+        assert!(super::parse_days("0").is_err());
+        assert!(super::parse_days("10..=3").is_err());
+        assert!(super::parse_days("20..=26").is_err());
+        assert!(super::parse_days("x").is_err());
+    }
+
+    #[test]
+    fn t3() {
+        let record = super::Record::new(5, Part::Two, "<text>".to_string(), Ok(Answer::Number(46)), Duration::from_micros(1500));
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"day":5,"part":2,"status":"ok","answer":46,"error":null,"input":"<text>","duration_ms":1.5}"#
+        );
+        let record = super::Record::new(5, Part::One, "<text>".to_string(), Err("no solution".to_string()), Duration::ZERO);
+        assert_eq!((record.status, record.error.as_deref()), ("error", Some("no solution")));
+    }
+
+    #[test]
+    fn t4() {
+        assert_eq!(super::parse_seconds("2.5"), Ok(Duration::from_millis(2500)));
+        assert!(super::parse_seconds("-1").is_err());
+        assert!(super::parse_seconds("soon").is_err());
+    }
+}
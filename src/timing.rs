@@ -0,0 +1,103 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+pub const PHASES: [&str; 4] = ["read", "parse", "part1", "part2"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub day: u32,
+    pub phase: &'static str,
+    pub duration: Duration,
+}
+
+struct Start {
+    day: u32,
+    at: Instant,
+}
+
+struct DayVisitor(Option<u32>);
+
+impl Visit for DayVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "day" {
+            self.0 = u32::try_from(value).ok();
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Timings(Arc<Mutex<Vec<Timing>>>);
+
+impl Timings {
+    pub fn snapshot(&self) -> Vec<Timing> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn duration(&self, day: u32, phase: &str) -> Duration {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.day == day && t.phase == phase)
+            .map(|t| t.duration)
+            .sum()
+    }
+}
+
+impl<S> Layer<S> for Timings
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !PHASES.contains(&attrs.metadata().name()) {
+            return;
+        }
+        let mut visitor = DayVisitor(None);
+        attrs.record(&mut visitor);
+        if let (Some(day), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(Start { day, at: Instant::now() });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        if let Some(start) = extensions.get::<Start>() {
+            self.0.lock().unwrap().push(Timing {
+                day: start.day,
+                phase: span.name(),
+                duration: start.at.elapsed(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timings;
+    use crate::Part;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn t1() {
+        let timings = Timings::default();
+        let subscriber = tracing_subscriber::registry().with(timings.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let input = std::fs::read_to_string("data/day9/input1.txt").unwrap();
+            crate::solve(9, &[Part::One, Part::Two], &input).unwrap();
+            tracing::info_span!("unrelated", day = 9).in_scope(|| ());
+        });
+        let phases: Vec<_> = timings.snapshot().iter().map(|t| (t.day, t.phase)).collect();
+        assert_eq!(phases, vec![(9, "parse"), (9, "part1"), (9, "part2")]);
+    }
+}